@@ -1,19 +1,142 @@
 use std::cell::{Cell, RefCell};
-use std::ffi::{c_void, CStr, CString};
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString, NulError};
+#[cfg(feature = "serde")]
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::{panic, process};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::{fmt, panic, process, ptr, str};
+
+#[cfg(feature = "serde")]
+use serde_crate::{de::DeserializeOwned, Deserialize, Serialize};
 
 thread_local! {
     static MAIN_THREAD: Cell<bool> = Cell::new(false);
+    static WINDOWS: RefCell<HashMap<usize, Window>> = RefCell::new(HashMap::new());
+    static RUN_CREATE: RefCell<Option<Box<dyn FnOnce() -> Window>>> = RefCell::new(None);
+    // The window whose closing ends `run_with`.
+    static RUN_WINDOW: Cell<Option<usize>> = Cell::new(None);
 }
 
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
+static STARTED: AtomicBool = AtomicBool::new(false);
+static NEXT_WINDOW_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+pub enum Error {
+    Null(NulError),
+    Script(String),
+    Closed,
+    NotMainThread,
+    InvalidIcon,
+    InvalidUrl,
+    InvalidBindName,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Null(ref err) => write!(f, "Null error: {}", err),
+            Error::Script(ref message) => write!(f, "Script error: {}", message),
+            Error::Closed => write!(f, "Window is closed"),
+            Error::NotMainThread => write!(f, "Not called from the main thread"),
+            Error::InvalidIcon => write!(f, "Icon could not be created"),
+            Error::InvalidUrl => write!(f, "URL could not be parsed"),
+            Error::InvalidBindName => write!(f, "Binding name contains ':'"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Null(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<NulError> for Error {
+    fn from(err: NulError) -> Error {
+        Error::Null(err)
+    }
+}
 
 pub trait Handler: 'static {
     fn handle(&mut self, window: Window, message: &str) {
         let _ = (window, message);
     }
+
+    /// Receives every message as raw bytes. The default implementation passes
+    /// valid UTF-8 straight to `handle` and decodes anything else lossily.
+    fn handle_bytes(&mut self, window: Window, bytes: &[u8]) {
+        self.handle(window, &String::from_utf8_lossy(bytes))
+    }
+
+    /// Called once, as soon as the window exists, so the handler can keep the
+    /// `Window` for later use.
+    fn on_created(&mut self, window: Window) {
+        let _ = window;
+    }
+
+    /// Called once the page's DOM is ready, before images and other
+    /// resources have finished loading.
+    fn on_dom_content_loaded(&mut self, window: Window) {
+        let _ = window;
+    }
+
+    /// Called when a navigation finishes; `ok` is false if it failed.
+    fn on_navigation_completed(&mut self, window: Window, uri: &str, ok: bool) {
+        let _ = (window, uri, ok);
+    }
+
+    /// Called for each key pressed in the page before the web view acts on
+    /// it, such as F5 reloading. Returning `true` swallows the key.
+    fn on_accelerator(&mut self, window: Window, key: Accelerator) -> bool {
+        let _ = (window, key);
+        false
+    }
+
+    /// Called with the native paths of files dropped onto the window.
+    fn on_file_drop(&mut self, window: Window, paths: Vec<PathBuf>) {
+        let _ = (window, paths);
+    }
+}
+
+/// A key together with the modifiers held down with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+    /// A Windows virtual-key code, such as `0x74` for F5 or `b'R' as u32`.
+    pub key: u32,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl Accelerator {
+    pub fn new(key: u32) -> Self {
+        Accelerator {
+            key,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    pub fn ctrl(key: u32) -> Self {
+        Accelerator {
+            ctrl: true,
+            ..Accelerator::new(key)
+        }
+    }
+
+    pub fn alt(key: u32) -> Self {
+        Accelerator {
+            alt: true,
+            ..Accelerator::new(key)
+        }
+    }
 }
 
 impl<F: FnMut(Window, &str) + 'static> Handler for F {
@@ -22,60 +145,252 @@ impl<F: FnMut(Window, &str) + 'static> Handler for F {
     }
 }
 
+#[cfg(feature = "serde")]
+pub struct JsonHandler<T, F> {
+    f: F,
+    phantom: PhantomData<fn(T)>,
+}
+
+#[cfg(feature = "serde")]
+impl<T, F> JsonHandler<T, F>
+where
+    T: DeserializeOwned + 'static,
+    F: FnMut(Window, serde_json::Result<T>) + 'static,
+{
+    pub fn new(f: F) -> Self {
+        JsonHandler {
+            f,
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, F> Handler for JsonHandler<T, F>
+where
+    T: DeserializeOwned + 'static,
+    F: FnMut(Window, serde_json::Result<T>) + 'static,
+{
+    fn handle(&mut self, window: Window, message: &str) {
+        (self.f)(window, serde_json::from_str(message))
+    }
+}
+
+const BIND_PREFIX: &str = "__webview_bind:";
+
+const BIND_RUNTIME: &str = r#"
+window.__webview_bindings = window.__webview_bindings || {
+  seq: 0,
+  pending: {},
+  call: function (name, args) {
+    var bindings = this;
+    return new Promise(function (resolve, reject) {
+      var seq = ++bindings.seq;
+      bindings.pending[seq] = { resolve: resolve, reject: reject };
+      window.external.notify('__webview_bind:' + seq + ':' + name + ':' + JSON.stringify(args));
+    });
+  },
+  complete: function (seq, ok, value) {
+    var promise = this.pending[seq];
+    delete this.pending[seq];
+    if (promise) {
+      (ok ? promise.resolve : promise.reject)(value);
+    }
+  }
+};
+"#;
+
+type Binding = Rc<RefCell<dyn FnMut(&str) -> Result<String, String>>>;
+
 #[derive(Clone)]
 pub struct Window {
+    id: usize,
     data: Rc<RefCell<Option<raw::webview>>>,
+    bindings: Rc<RefCell<HashMap<String, Binding>>>,
+    title: Rc<RefCell<String>>,
+}
+
+/// A `Send` reference to a `Window` for use from other threads. Every call is
+/// dispatched to the main thread and does nothing once the window is closed.
+#[derive(Clone)]
+pub struct WindowHandle {
+    id: usize,
 }
 
-type Data = (Window, Box<dyn Handler>);
+struct Data {
+    window: Window,
+    handler: Box<dyn Handler>,
+    on_close: Option<Box<dyn FnMut(Window) -> bool>>,
+    disable_accelerators: Vec<Accelerator>,
+}
 
 impl Window {
     pub fn new(opts: Options) -> Self {
-        assert_main();
+        Self::try_new(opts).unwrap()
+    }
+
+    /// Like `new`, but returns an error instead of panicking when called off
+    /// the main thread or given a `Content::Url` that isn't a valid URL.
+    pub fn try_new(opts: Options) -> Result<Self, Error> {
+        if !is_main() {
+            return Err(Error::NotMainThread);
+        }
+
+        if let Some(ref icon) = opts.icon {
+            check_icon(&icon.rgba, icon.width, icon.height)?;
+        }
+
+        let (url, html) = match opts.content {
+            Some(Content::Url(ref url)) => (Some(CString::new(url.as_str())?), None),
+            Some(Content::Html(ref html)) => (None, Some(CString::new(html.as_str())?)),
+            None => (None, None),
+        };
 
         let this = Window {
+            id: NEXT_WINDOW_ID.fetch_add(1, Ordering::Relaxed),
             data: Rc::new(RefCell::new(None)),
+            bindings: Rc::new(RefCell::new(HashMap::new())),
+            title: Rc::new(RefCell::new(String::new())),
         };
 
         let handler = opts.handler.unwrap_or(Box::new(|_, _: &_| {}));
+        let data = Box::<Data>::into_raw(Box::new(Data {
+            window: this.clone(),
+            handler,
+            on_close: opts.on_close,
+            disable_accelerators: opts.disable_accelerators,
+        }));
 
         let opts = raw::webview_options {
             initial_width: opts.initial_width,
             initial_height: opts.initial_height,
             minimum_width: opts.minimum_width,
             minimum_height: opts.minimum_height,
+            maximum_width: opts.maximum_width,
+            maximum_height: opts.maximum_height,
+
+            has_position: opts.position.is_some(),
+            x: opts.position.map_or(0, |(x, _)| x),
+            y: opts.position.map_or(0, |(_, y)| y),
+            center: opts.center,
 
             borderless: opts.borderless,
+            transparent: opts.transparent,
+            always_on_top: opts.always_on_top,
+            visible: opts.visible,
             debug: opts.debug,
 
-            data: Box::<Data>::into_raw(Box::new((this.clone(), handler))) as _,
+            url: url.as_ref().map_or(ptr::null(), |url| url.as_ptr()),
+            html: html.as_ref().map_or(ptr::null(), |html| html.as_ptr()),
+            icon: opts
+                .icon
+                .as_ref()
+                .map_or(ptr::null(), |icon| icon.rgba.as_ptr()),
+            icon_width: opts.icon.as_ref().map_or(0, |icon| icon.width as usize),
+            icon_height: opts.icon.as_ref().map_or(0, |icon| icon.height as usize),
+
+            data: data as _,
+            closing: Some(closing),
             closed: Some(closed),
             message: Some(message),
+            dom_content_loaded: Some(dom_content_loaded),
+            navigation_completed: Some(navigation_completed),
+            accelerator: Some(accelerator),
+            file_drop: Some(file_drop),
         };
 
         let raw = unsafe { raw::webview_new(opts) };
+        if raw.is_null() {
+            unsafe { drop(Box::<Data>::from_raw(data)) };
+            return Err(Error::InvalidUrl);
+        }
         this.data.replace(Some(raw));
+        WINDOWS.with(|windows| windows.borrow_mut().insert(this.id, this.clone()));
+
+        unsafe { (*data).handler.on_created(this.clone()) };
+
+        unsafe extern "C" fn closing(data: *mut c_void) -> bool {
+            abort_on_panic(|| {
+                let data = data as *mut Data;
+                match (*data).on_close {
+                    Some(ref mut on_close) => on_close((*data).window.clone()),
+                    None => true,
+                }
+            })
+        }
 
         unsafe extern "C" fn closed(data: *mut c_void) {
             abort_on_panic(|| {
-                let _ = Box::<Data>::from_raw(data as _);
+                let data = Box::<Data>::from_raw(data as _);
+                data.window.data.replace(None);
+                WINDOWS.with(|windows| windows.borrow_mut().remove(&data.window.id));
+                if RUN_WINDOW.with(Cell::get) == Some(data.window.id) {
+                    raw::webview_exit();
+                }
             });
         }
 
         unsafe extern "C" fn message(data: *mut c_void, message: *const i8) {
             abort_on_panic(|| {
                 let data = data as *mut Data;
-
-                match CStr::from_ptr(message).to_str() {
-                    Ok(message) => {
-                        (*data).1.handle((*data).0.clone(), message);
-                    }
-                    Err(e) => {}
+                let bytes = CStr::from_ptr(message).to_bytes();
+                if !(*data).window.call_binding(bytes) {
+                    (*data).handler.handle_bytes((*data).window.clone(), bytes);
                 }
             });
         }
 
-        this
+        unsafe extern "C" fn dom_content_loaded(data: *mut c_void) {
+            abort_on_panic(|| {
+                let data = data as *mut Data;
+                (*data)
+                    .handler
+                    .on_dom_content_loaded((*data).window.clone());
+            });
+        }
+
+        unsafe extern "C" fn navigation_completed(data: *mut c_void, uri: *const i8, ok: bool) {
+            abort_on_panic(|| {
+                let data = data as *mut Data;
+                let uri = CStr::from_ptr(uri).to_string_lossy();
+                (*data)
+                    .handler
+                    .on_navigation_completed((*data).window.clone(), &uri, ok);
+            });
+        }
+
+        unsafe extern "C" fn accelerator(
+            data: *mut c_void,
+            key: u32,
+            ctrl: bool,
+            alt: bool,
+            shift: bool,
+        ) -> bool {
+            abort_on_panic(|| {
+                let data = data as *mut Data;
+                let key = Accelerator {
+                    key,
+                    ctrl,
+                    alt,
+                    shift,
+                };
+                (*data).disable_accelerators.contains(&key)
+                    || (*data).handler.on_accelerator((*data).window.clone(), key)
+            })
+        }
+
+        unsafe extern "C" fn file_drop(data: *mut c_void, paths: *const *const i8, count: usize) {
+            abort_on_panic(|| {
+                let data = data as *mut Data;
+                let paths = std::slice::from_raw_parts(paths, count)
+                    .iter()
+                    .map(|&path| PathBuf::from(CStr::from_ptr(path).to_string_lossy().into_owned()))
+                    .collect();
+                (*data).handler.on_file_drop((*data).window.clone(), paths);
+            });
+        }
+
+        Ok(this)
     }
 
     pub fn with_handler(handler: impl Handler) -> Self {
@@ -85,29 +400,515 @@ impl Window {
         })
     }
 
+    /// Evaluates `s` in the page. Does nothing if `s` contains a NUL byte; use
+    /// `try_eval` to observe that.
     pub fn eval<I: Into<String>>(&self, s: I) {
+        let _ = self.try_eval(s);
+    }
+
+    pub fn try_eval<I: Into<String>>(&self, s: I) -> Result<(), Error> {
+        let s = CString::new(s.into())?;
         if let Some(data) = *self.data.borrow_mut() {
-            let s = string_to_cstring(s);
             unsafe {
                 raw::webview_eval(data, s.as_ptr());
             }
         }
+        Ok(())
     }
 
+    /// Exposes `f` to the page as `window[name]`, a function returning a Promise.
+    /// `f` receives the call's arguments as a JSON array; `Ok` must hold JSON,
+    /// which the promise resolves to, and `Err` rejects it with an `Error`.
+    /// Names containing `:` are refused with `Error::InvalidBindName`, as calls
+    /// are routed by splitting on it.
+    pub fn bind<F>(&self, name: &str, f: F) -> Result<(), Error>
+    where
+        F: FnMut(&str) -> Result<String, String> + 'static,
+    {
+        if name.contains(':') {
+            return Err(Error::InvalidBindName);
+        }
+        let data = match *self.data.borrow_mut() {
+            Some(data) => data,
+            None => return Ok(()),
+        };
+
+        let first = {
+            let mut bindings = self.bindings.borrow_mut();
+            let first = bindings.is_empty();
+            bindings.insert(name.to_owned(), Rc::new(RefCell::new(f)));
+            first
+        };
+
+        let mut script = String::new();
+        if first {
+            script.push_str(BIND_RUNTIME);
+        }
+        script.push_str(&format!(
+            "window[{name}] = function () {{ return window.__webview_bindings.call({name}, Array.prototype.slice.call(arguments)); }};",
+            name = js_string(name)
+        ));
+
+        // `js_string` escapes NUL, so the script never contains one.
+        let script = CString::new(script).unwrap();
+        unsafe {
+            raw::webview_add_init_script(data, script.as_ptr());
+            raw::webview_eval(data, script.as_ptr());
+        }
+        Ok(())
+    }
+
+    fn call_binding(&self, message: &[u8]) -> bool {
+        let message = match str::from_utf8(message) {
+            Ok(message) if message.starts_with(BIND_PREFIX) => &message[BIND_PREFIX.len()..],
+            _ => return false,
+        };
+
+        let mut parts = message.splitn(3, ':');
+        let (seq, name, args) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(seq), Some(name), Some(args)) => (seq, name, args),
+            _ => return false,
+        };
+        let seq = match seq.parse::<u64>() {
+            Ok(seq) => seq,
+            Err(_) => return false,
+        };
+        let binding = match self.bindings.borrow().get(name) {
+            Some(binding) => binding.clone(),
+            None => return false,
+        };
+
+        let result = (&mut *binding.borrow_mut())(args);
+        self.eval(match result {
+            Ok(value) => format!(
+                "window.__webview_bindings.complete({}, true, JSON.parse({}));",
+                seq,
+                js_string(&value)
+            ),
+            Err(message) => format!(
+                "window.__webview_bindings.complete({}, false, new Error({}));",
+                seq,
+                js_string(&message)
+            ),
+        });
+        true
+    }
+
+    /// Evaluates `s` in the page's global scope and returns the result converted
+    /// to a string. A thrown exception is returned as `Error::Script`.
+    pub fn eval_result<I: Into<String>>(&self, s: I) -> Result<String, Error> {
+        let script = CString::new(result_script(&s.into()))?;
+
+        // The borrow must not be held while the native side pumps messages.
+        let data = *self.data.borrow_mut();
+        let data = data.ok_or(Error::Closed)?;
+
+        let (ok, value) = unsafe {
+            let mut result = ptr::null_mut();
+            let ok = raw::webview_eval_result(data, script.as_ptr(), &mut result);
+            let value = CStr::from_ptr(result).to_string_lossy().into_owned();
+            raw::webview_string_free(result);
+            (ok, value)
+        };
+
+        parse_result(ok, value)
+    }
+
+    /// Like `eval_result`, but returns immediately and passes the result to
+    /// `f` on the main thread once the script has run. `f` gets
+    /// `Error::Closed` if the window closes first.
+    pub fn eval_async<I, F>(&self, s: I, f: F)
+    where
+        I: Into<String>,
+        F: FnOnce(Result<String, Error>) + 'static,
+    {
+        let script = match CString::new(result_script(&s.into())) {
+            Ok(script) => script,
+            Err(err) => return f(Err(err.into())),
+        };
+        let data = *self.data.borrow_mut();
+        let data = match data {
+            Some(data) => data,
+            None => return f(Err(Error::Closed)),
+        };
+
+        unsafe {
+            raw::webview_eval_async(
+                data,
+                script.as_ptr(),
+                Box::<F>::into_raw(Box::new(f)) as _,
+                Some(done::<F>),
+            );
+        }
+
+        unsafe extern "C" fn done<F: FnOnce(Result<String, Error>)>(
+            data: *mut c_void,
+            ok: bool,
+            result: *const i8,
+        ) {
+            abort_on_panic(|| {
+                let f = Box::<F>::from_raw(data as _);
+                if result.is_null() {
+                    f(Err(Error::Closed));
+                } else {
+                    let value = CStr::from_ptr(result).to_string_lossy().into_owned();
+                    f(parse_result(ok, value));
+                }
+            });
+        }
+    }
+
+    /// Serializes `value` to JSON and delivers it to the page as the `data` of a
+    /// `message` event dispatched on `window`.
+    #[cfg(feature = "serde")]
+    pub fn post_message<T: Serialize>(&self, value: &T) -> serde_json::Result<()> {
+        self.eval(message_script(value)?);
+        Ok(())
+    }
+
+    /// Navigates to `s`. Does nothing if `s` contains a NUL byte; use
+    /// `try_load` to observe that.
     pub fn load<I: Into<String>>(&self, s: I) {
+        let _ = self.try_load(s);
+    }
+
+    pub fn try_load<I: Into<String>>(&self, s: I) -> Result<(), Error> {
+        let s = CString::new(s.into())?;
         if let Some(data) = *self.data.borrow_mut() {
-            let s = string_to_cstring(s);
             unsafe {
                 raw::webview_load(data, s.as_ptr());
             }
         }
+        Ok(())
+    }
+
+    pub fn set_html<I: Into<String>>(&self, html: I) {
+        let _ = self.try_load(html);
     }
 
+    /// Sets the window title. Does nothing if `s` contains a NUL byte; use
+    /// `try_title` to observe that.
     pub fn title<I: Into<String>>(&self, s: I) {
+        let _ = self.try_title(s);
+    }
+
+    pub fn try_title<I: Into<String>>(&self, s: I) -> Result<(), Error> {
+        let s = s.into();
+        let title = CString::new(s.as_str())?;
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_title(data, title.as_ptr());
+            }
+        }
+        self.title.replace(s);
+        Ok(())
+    }
+
+    /// The window's current title, or the last one set once it is closed.
+    pub fn get_title(&self) -> String {
+        if let Some(data) = *self.data.borrow_mut() {
+            let title = unsafe {
+                let title = raw::webview_get_title(data);
+                let value = CStr::from_ptr(title).to_string_lossy().into_owned();
+                raw::webview_string_free(title);
+                value
+            };
+            self.title.replace(title.clone());
+            title
+        } else {
+            self.title.borrow().clone()
+        }
+    }
+
+    /// The native window, or null once it is closed.
+    pub fn hwnd(&self) -> *mut c_void {
+        match *self.data.borrow_mut() {
+            Some(data) => unsafe { raw::webview_get_hwnd(data) },
+            None => ptr::null_mut(),
+        }
+    }
+
+    pub fn reload(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_reload(data, false);
+            }
+        }
+    }
+
+    pub fn reload_ignoring_cache(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_reload(data, true);
+            }
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_stop(data);
+            }
+        }
+    }
+
+    pub fn go_back(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_go_back(data);
+            }
+        }
+    }
+
+    pub fn go_forward(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_go_forward(data);
+            }
+        }
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        match *self.data.borrow_mut() {
+            Some(data) => unsafe { raw::webview_can_go_back(data) },
+            None => false,
+        }
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        match *self.data.borrow_mut() {
+            Some(data) => unsafe { raw::webview_can_go_forward(data) },
+            None => false,
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
         if let Some(data) = *self.data.borrow_mut() {
-            let s = string_to_cstring(s);
+            let (mut width, mut height) = (0, 0);
             unsafe {
-                raw::webview_title(data, s.as_ptr());
+                raw::webview_size(data, &mut width, &mut height);
+            }
+            (width, height)
+        } else {
+            (0, 0)
+        }
+    }
+
+    /// Returns 1.0 once the window is closed.
+    pub fn zoom_factor(&self) -> f64 {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe { raw::webview_zoom_factor(data) }
+        } else {
+            1.0
+        }
+    }
+
+    /// Sets the page zoom, clamped to `0.25..=5.0`.
+    pub fn set_zoom_factor(&self, factor: f64) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_set_zoom_factor(data, factor.max(0.25).min(5.0));
+            }
+        }
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        if let Some(data) = *self.data.borrow_mut() {
+            let (mut x, mut y) = (0, 0);
+            unsafe {
+                raw::webview_position(data, &mut x, &mut y);
+            }
+            (x, y)
+        } else {
+            (0, 0)
+        }
+    }
+
+    pub fn set_position(&self, x: i32, y: i32) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_set_position(data, x, y);
+            }
+        }
+    }
+
+    /// The window's restored geometry and zoom, to be passed to `apply_state`,
+    /// for example on the next launch. A closed window reports zeros.
+    pub fn save_state(&self) -> WindowState {
+        let zoom = self.zoom_factor();
+        if let Some(data) = *self.data.borrow_mut() {
+            let (mut x, mut y, mut width, mut height, mut maximized) = (0, 0, 0, 0, false);
+            unsafe {
+                raw::webview_restored_bounds(
+                    data,
+                    &mut x,
+                    &mut y,
+                    &mut width,
+                    &mut height,
+                    &mut maximized,
+                );
+            }
+            WindowState {
+                pos: (x, y),
+                size: (width, height),
+                maximized,
+                zoom,
+            }
+        } else {
+            WindowState {
+                pos: (0, 0),
+                size: (0, 0),
+                maximized: false,
+                zoom,
+            }
+        }
+    }
+
+    pub fn apply_state(&self, state: &WindowState) {
+        self.set_position(state.pos.0, state.pos.1);
+        self.set_size(state.size.0, state.size.1);
+        self.set_zoom_factor(state.zoom);
+        if state.maximized {
+            self.maximize();
+        }
+    }
+
+    /// Centers the window in the work area of the monitor under the cursor.
+    pub fn center_on_monitor(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_center_on_monitor(data);
+            }
+        }
+    }
+
+    pub fn set_size(&self, width: usize, height: usize) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_set_size(data, width, height);
+            }
+        }
+    }
+
+    pub fn set_min_size(&self, width: usize, height: usize) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_set_min_size(data, width, height);
+            }
+        }
+    }
+
+    /// Passing zero for either dimension removes that bound.
+    pub fn set_max_size(&self, width: usize, height: usize) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_set_max_size(data, width, height);
+            }
+        }
+    }
+
+    pub fn set_always_on_top(&self, on: bool) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_set_always_on_top(data, on);
+            }
+        }
+    }
+
+    /// Sets the title bar and taskbar icon from `width` × `height` RGBA pixels,
+    /// top row first. The image is scaled to the small and large icon sizes.
+    pub fn set_icon(&self, rgba: &[u8], width: u32, height: u32) -> Result<(), Error> {
+        check_icon(rgba, width, height)?;
+        let data = *self.data.borrow_mut();
+        let data = data.ok_or(Error::Closed)?;
+        if unsafe { raw::webview_set_icon(data, rgba.as_ptr(), width as usize, height as usize) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidIcon)
+        }
+    }
+
+    /// Sets the title bar and taskbar icon from an `.ico` file, picking the
+    /// best match for each size.
+    pub fn set_icon_from_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref().to_str().ok_or(Error::InvalidIcon)?;
+        let path = CString::new(path)?;
+        let data = *self.data.borrow_mut();
+        let data = data.ok_or(Error::Closed)?;
+        if unsafe { raw::webview_set_icon_from_file(data, path.as_ptr()) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidIcon)
+        }
+    }
+
+    /// Starts moving the window as if its title bar had been pressed. Meant
+    /// for borderless windows; elements styled `-webkit-app-region: drag`
+    /// inline, or marked `data-app-region="drag"`, do this automatically.
+    pub fn begin_drag(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_begin_drag(data);
+            }
+        }
+    }
+
+    /// Launches the Edge DevTools app, which attaches to the window on its
+    /// own. Does nothing unless `Options::debug` was set. There is no way to
+    /// close the app again from here, so the user closes it themselves.
+    pub fn open_dev_tools(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_open_dev_tools(data);
+            }
+        }
+    }
+
+    pub fn show(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_show(data);
+            }
+        }
+    }
+
+    /// Hides the window without closing it or unloading its page.
+    pub fn hide(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_hide(data);
+            }
+        }
+    }
+
+    pub fn minimize(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_minimize(data);
+            }
+        }
+    }
+
+    pub fn maximize(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_maximize(data);
+            }
+        }
+    }
+
+    pub fn restore(&self) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_restore(data);
+            }
+        }
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        if let Some(data) = *self.data.borrow_mut() {
+            unsafe {
+                raw::webview_set_fullscreen(data, fullscreen);
             }
         }
     }
@@ -127,6 +928,66 @@ impl Window {
             }
         }
     }
+
+    pub fn handle(&self) -> WindowHandle {
+        WindowHandle { id: self.id }
+    }
+}
+
+impl WindowHandle {
+    /// Runs `f` with the window on the main thread, unless it has closed by then.
+    pub fn dispatch<F: FnOnce(&Window) + Send + 'static>(&self, f: F) {
+        let id = self.id;
+        dispatch(move || {
+            let window = WINDOWS.with(|windows| windows.borrow().get(&id).cloned());
+            if let Some(window) = window {
+                f(&window);
+            }
+        });
+    }
+
+    pub fn eval<I: Into<String>>(&self, s: I) {
+        let s = s.into();
+        self.dispatch(move |window| window.eval(s));
+    }
+
+    pub fn load<I: Into<String>>(&self, s: I) {
+        let s = s.into();
+        self.dispatch(move |window| window.load(s));
+    }
+
+    pub fn set_html<I: Into<String>>(&self, html: I) {
+        let html = html.into();
+        self.dispatch(move |window| window.set_html(html));
+    }
+
+    pub fn title<I: Into<String>>(&self, s: I) {
+        let s = s.into();
+        self.dispatch(move |window| window.title(s));
+    }
+
+    /// Like `Window::post_message`. `value` is serialized on the calling thread.
+    #[cfg(feature = "serde")]
+    pub fn post_message<T: Serialize>(&self, value: &T) -> serde_json::Result<()> {
+        self.eval(message_script(value)?);
+        Ok(())
+    }
+
+    pub fn close(&self) {
+        self.dispatch(|window| window.close());
+    }
+}
+
+#[cfg(feature = "serde")]
+fn message_script<T: Serialize>(value: &T) -> serde_json::Result<String> {
+    // U+2028 and U+2029 are valid in JSON but not in JavaScript string literals.
+    let json = serde_json::to_string(value)?
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029");
+    Ok(format!(
+        "window.dispatchEvent(new MessageEvent('message', {{ data: {} }}));",
+        json
+    ))
 }
 
 impl Default for Window {
@@ -135,16 +996,92 @@ impl Default for Window {
     }
 }
 
+#[cfg(feature = "raw-window-handle")]
+unsafe impl raw_window_handle::HasRawWindowHandle for Window {
+    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
+        raw_window_handle::RawWindowHandle::Windows(raw_window_handle::windows::WindowsHandle {
+            hwnd: self.hwnd(),
+            ..raw_window_handle::windows::WindowsHandle::empty()
+        })
+    }
+}
+
+/// A window's geometry and zoom, from `Window::save_state`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct WindowState {
+    /// The outer top-left corner of the restored window, in screen coordinates.
+    pub pos: (i32, i32),
+    /// The client size of the restored window.
+    pub size: (usize, usize),
+    pub maximized: bool,
+    pub zoom: f64,
+}
+
+/// Straight RGBA pixels, top row first, for `Options::icon`.
+pub struct Icon {
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn check_icon(rgba: &[u8], width: u32, height: u32) -> Result<(), Error> {
+    if width == 0 || height == 0 || rgba.len() as u64 != width as u64 * height as u64 * 4 {
+        Err(Error::InvalidIcon)
+    } else {
+        Ok(())
+    }
+}
+
+/// What a window shows as soon as it is created.
+pub enum Content {
+    Url(String),
+    Html(String),
+}
+
 pub struct Options {
     pub initial_width: usize,
     pub initial_height: usize,
     pub minimum_width: usize,
     pub minimum_height: usize,
+    /// Zero means the window can grow without bound.
+    pub maximum_width: usize,
+    pub maximum_height: usize,
+    /// The screen position of the window's top-left corner. Coordinates can
+    /// be negative on monitors left of or above the primary one. `None` lets
+    /// Windows choose.
+    pub position: Option<(i32, i32)>,
+    /// Centers the window in the primary monitor's work area, the part not
+    /// covered by the taskbar. Ignored if `position` is set.
+    pub center: bool,
 
     pub borderless: bool,
+    /// Lets the desktop show through wherever the page has no background.
+    /// Falls back to an opaque window if that isn't supported.
+    pub transparent: bool,
+    pub always_on_top: bool,
+    /// Set to `false` to create the window hidden and `show` it once its
+    /// content has loaded.
+    pub visible: bool,
     pub debug: bool,
 
+    /// Navigated to while the window is being created, so it never has to
+    /// show a blank page first.
+    pub content: Option<Content>,
+    pub icon: Option<Icon>,
+
     pub handler: Option<Box<dyn Handler>>,
+    /// Called when the user or `Window::close` asks to close the window;
+    /// returning `false` keeps it open. Ignored while the application is
+    /// exiting.
+    pub on_close: Option<Box<dyn FnMut(Window) -> bool>>,
+    /// Keys swallowed before the page or the web view sees them, to stop a
+    /// kiosk from being reloaded or printed, for example.
+    pub disable_accelerators: Vec<Accelerator>,
 }
 
 impl Default for Options {
@@ -154,16 +1091,34 @@ impl Default for Options {
             initial_height: 480,
             minimum_width: 480,
             minimum_height: 360,
+            maximum_width: 0,
+            maximum_height: 0,
+            position: None,
+            center: false,
 
             borderless: false,
+            transparent: false,
+            always_on_top: false,
+            visible: true,
             debug: true,
 
+            content: None,
+            icon: None,
+
             handler: None,
+            on_close: None,
+            disable_accelerators: Vec::new(),
         }
     }
 }
 
+/// Runs the message loop, calling `cb` once the runtime is ready. Panics if
+/// the runtime has already been started, by this or `run_with`.
 pub unsafe fn start(cb: fn()) {
+    assert!(
+        !STARTED.swap(true, Ordering::SeqCst),
+        "webview runtime started more than once"
+    );
     static mut INIT: Option<fn()> = None;
     INIT = Some(cb);
 
@@ -182,6 +1137,31 @@ pub unsafe fn start(cb: fn()) {
     raw::webview_start(Some(init));
 }
 
+/// Starts the runtime, creates a window with `create` and blocks until that
+/// window closes or `exit` is called. Other windows can be opened from
+/// `create` or later, but closing them does not end the loop. Replaces `start`
+/// for applications with one main window, and can only be called once.
+pub fn run_with<F: FnOnce() -> Window + 'static>(create: F) {
+    RUN_CREATE.with(|run| run.replace(Some(Box::new(create))));
+
+    fn init() {
+        let create = RUN_CREATE.with(|run| run.borrow_mut().take()).unwrap();
+        let window = create();
+        if window.data.borrow().is_some() {
+            RUN_WINDOW.with(|run| run.set(Some(window.id)));
+        } else {
+            exit();
+        }
+    }
+
+    unsafe { start(init) }
+}
+
+/// Opens a window with `options` and blocks until it closes. See `run_with`.
+pub fn run(options: Options) {
+    run_with(move || Window::new(options))
+}
+
 pub fn exit() {
     assert_main();
 
@@ -190,11 +1170,17 @@ pub fn exit() {
     }
 }
 
+/// Runs `f` on the main thread. Once `exit` has been called, `f` is dropped
+/// instead.
 pub fn dispatch<F: FnOnce() + Send>(f: F) {
     assert_initialized();
 
     unsafe {
-        raw::webview_dispatch(Box::<F>::into_raw(Box::new(f)) as _, Some(execute::<F>));
+        raw::webview_dispatch(
+            Box::<F>::into_raw(Box::new(f)) as _,
+            Some(execute::<F>),
+            Some(cancel::<F>),
+        );
     }
 
     unsafe extern "C" fn execute<F: FnOnce() + Send>(data: *mut c_void) {
@@ -202,11 +1188,19 @@ pub fn dispatch<F: FnOnce() + Send>(f: F) {
             Box::<F>::from_raw(data as _)();
         });
     }
+
+    // Drops `f` without running it, when the application exits first.
+    unsafe extern "C" fn cancel<F: FnOnce() + Send>(data: *mut c_void) {
+        abort_on_panic(|| {
+            drop(Box::<F>::from_raw(data as _));
+        });
+    }
 }
 
-fn abort_on_panic<F: FnOnce() + panic::UnwindSafe>(f: F) {
-    if panic::catch_unwind(f).is_err() {
-        process::abort();
+fn abort_on_panic<R, F: FnOnce() -> R + panic::UnwindSafe>(f: F) -> R {
+    match panic::catch_unwind(f) {
+        Ok(result) => result,
+        Err(_) => process::abort(),
     }
 }
 
@@ -215,13 +1209,93 @@ fn assert_initialized() {
 }
 
 fn assert_main() {
-    MAIN_THREAD.with(|initialized| {
-        assert!(initialized.get());
-    });
+    assert!(is_main());
 }
 
-fn string_to_cstring<I: Into<String>>(s: I) -> CString {
-    CString::new(s.into()).unwrap()
+fn is_main() -> bool {
+    MAIN_THREAD.with(|initialized| initialized.get())
+}
+
+// Wraps `s` so its result or exception comes back as a string prefixed with
+// `+` or `-`, which `parse_result` takes apart.
+fn result_script(s: &str) -> String {
+    format!(
+        "(function () {{ try {{ return '+' + (0, eval)({}); }} catch (e) {{ return '-' + e; }} }})()",
+        js_string(s)
+    )
+}
+
+fn parse_result(ok: bool, value: String) -> Result<String, Error> {
+    match (ok, value.chars().next()) {
+        (true, Some('+')) => Ok(value[1..].to_owned()),
+        (true, Some('-')) => Err(Error::Script(value[1..].to_owned())),
+        _ => Err(Error::Script(value)),
+    }
+}
+
+/// Quotes `s` as a JavaScript string literal, safe to splice into a script.
+/// Also used by the `edge-webview` crate, so both escape the same way.
+pub fn js_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\u0000"),
+            '\u{2028}' => out.push_str("\\u2028"),
+            '\u{2029}' => out.push_str("\\u2029"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static RAN: AtomicUsize = AtomicUsize::new(0);
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    const QUEUED: usize = 8;
+
+    struct Counted;
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn exit_drops_pending_dispatches_without_running_them() {
+        fn init() {
+            for _ in 0..QUEUED {
+                let counted = Counted;
+                dispatch(move || {
+                    let _counted = counted;
+                    RAN.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+            exit();
+        }
+
+        unsafe { start(init) };
+
+        assert_eq!(RAN.load(Ordering::SeqCst), 0);
+        assert_eq!(DROPPED.load(Ordering::SeqCst), QUEUED);
+    }
+
+    #[test]
+    fn js_string_escapes_everything_that_ends_a_literal() {
+        assert_eq!(
+            js_string("\"\\\n\r\0\u{2028}\u{2029}'"),
+            r#""\"\\\n\r\u0000\u2028\u2029'""#
+        );
+    }
 }
 
 mod raw {
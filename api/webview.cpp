@@ -2,7 +2,16 @@
 #include <sdkddkver.h>
 #include <objbase.h>
 #include <Windows.h>
+#include <shellapi.h>
+#include <algorithm>
+#include <memory>
+#include <mutex>
+#include <string>
+#include <vector>
 #include <winrt/Windows.Foundation.h>
+#include <winrt/Windows.System.h>
+#include <winrt/Windows.UI.h>
+#include <winrt/Windows.UI.Core.h>
 #include <winrt/Windows.Web.UI.Interop.h>
 
 #include "webview.h"
@@ -14,7 +23,33 @@ using namespace Windows::Web::UI::Interop;
 
 const LPCSTR WINDOW_CLASS = "BORING";
 const UINT WM_APP_DISPATCH = WM_APP;
+// Painted behind a transparent web view and keyed out of the layered window.
+const COLORREF TRANSPARENT_KEY = RGB(255, 0, 255);
+const char *const DRAG_MESSAGE = "__webview_drag";
+// EdgeHTML drops `-webkit-app-region` from computed styles, so look for it in
+// inline styles, or for a `data-app-region="drag"` attribute.
+const wchar_t *const DRAG_SCRIPT = LR"(
+document.addEventListener('mousedown', function (e) {
+    if (e.button !== 0) return;
+    for (var el = e.target; el && el.getAttribute; el = el.parentNode) {
+        var region = el.getAttribute('data-app-region');
+        if (!region) {
+            var m = /-webkit-app-region\s*:\s*(drag|no-drag)/.exec(el.getAttribute('style') || '');
+            region = m && m[1];
+        }
+        if (region === 'no-drag') return;
+        if (region === 'drag') {
+            e.preventDefault();
+            window.external.notify('__webview_drag');
+            return;
+        }
+    }
+});
+)";
 static DWORD MAIN_THREAD;
+static bool EXITING = false;
+// Set while Windows is asking to end the session, which can still be cancelled.
+static bool ENDING_SESSION = false;
 static WebViewControlProcess WEBVIEWS{nullptr};
 
 template <typename T>
@@ -48,6 +83,59 @@ struct Dispatch
 {
     void *data;
     void (*func)(void *data);
+    void (*cancel)(void *data);
+};
+
+// Guards STOPPED so no dispatch can be posted after the queue is drained.
+static std::mutex DISPATCH_LOCK;
+static bool STOPPED = false;
+
+// Builds an icon from straight RGBA pixels, top row first.
+static HICON icon_from_rgba(const unsigned char *rgba, size_t width, size_t height)
+{
+    BITMAPV5HEADER header = {};
+    header.bV5Size = sizeof header;
+    header.bV5Width = (LONG)width;
+    header.bV5Height = -(LONG)height;
+    header.bV5Planes = 1;
+    header.bV5BitCount = 32;
+    header.bV5Compression = BI_BITFIELDS;
+    header.bV5RedMask = 0x00FF0000;
+    header.bV5GreenMask = 0x0000FF00;
+    header.bV5BlueMask = 0x000000FF;
+    header.bV5AlphaMask = 0xFF000000;
+
+    void *bits = nullptr;
+    HDC dc = GetDC(nullptr);
+    HBITMAP color = CreateDIBSection(dc, (BITMAPINFO *)&header, DIB_RGB_COLORS, &bits, nullptr, 0);
+    ReleaseDC(nullptr, dc);
+    if (!color)
+        return nullptr;
+
+    unsigned char *out = (unsigned char *)bits;
+    for (size_t i = 0; i < width * height * 4; i += 4)
+    {
+        out[i] = rgba[i + 2];
+        out[i + 1] = rgba[i + 1];
+        out[i + 2] = rgba[i];
+        out[i + 3] = rgba[i + 3];
+    }
+
+    // The alpha channel decides transparency; the mask only has to exist.
+    HBITMAP mask = CreateBitmap((int)width, (int)height, 1, 1, nullptr);
+    ICONINFO info = {TRUE, 0, 0, mask, color};
+    HICON icon = CreateIconIndirect(&info);
+    DeleteObject(mask);
+    DeleteObject(color);
+    return icon;
+}
+
+// `done` is cleared once called, so a completion arriving after the window
+// is gone does nothing.
+struct PendingEval
+{
+    void *data;
+    void (*done)(void *data, bool ok, const char *result);
 };
 
 struct _webview
@@ -56,14 +144,37 @@ struct _webview
     WebViewControl webview = nullptr;
     webview_options opts;
 
+    bool fullscreen = false;
+    RECT saved_rect;
+    LONG saved_style = -1;
+
+    bool transparent = false;
+
+    HICON small_icon = nullptr;
+    HICON big_icon = nullptr;
+
+    std::vector<std::shared_ptr<PendingEval>> pending_evals;
+
     _webview(webview_options opts) : opts(opts)
     {
-        hwnd = CreateWindow(
+        if (opts.center && !opts.has_position)
+        {
+            MONITORINFO mi;
+            mi.cbSize = sizeof mi;
+            GetMonitorInfo(MonitorFromPoint({0, 0}, MONITOR_DEFAULTTOPRIMARY), &mi);
+            RECT work = mi.rcWork;
+            opts.has_position = true;
+            opts.x = work.left + (work.right - work.left - (LONG)opts.initial_width) / 2;
+            opts.y = work.top + (work.bottom - work.top - (LONG)opts.initial_height) / 2;
+        }
+
+        hwnd = CreateWindowEx(
+            (opts.transparent ? WS_EX_LAYERED : 0) | (opts.always_on_top ? WS_EX_TOPMOST : 0),
             WINDOW_CLASS,
             "",
             opts.borderless ? 0 : WS_OVERLAPPEDWINDOW,
-            CW_USEDEFAULT,
-            CW_USEDEFAULT,
+            opts.has_position ? opts.x : CW_USEDEFAULT,
+            opts.has_position ? opts.y : CW_USEDEFAULT,
             opts.initial_width,
             opts.initial_height,
             nullptr,
@@ -71,61 +182,245 @@ struct _webview
             GetModuleHandle(nullptr),
             nullptr);
 
+        if (opts.transparent)
+            transparent = SetLayeredWindowAttributes(hwnd, TRANSPARENT_KEY, 0, LWA_COLORKEY);
+
         webview = block(WEBVIEWS.CreateWebViewControlAsync((int64_t)hwnd, getClientRect(hwnd)));
 
+        if (transparent)
+        {
+            try
+            {
+                webview.DefaultBackgroundColor(Windows::UI::Color{0, 255, 255, 255});
+            }
+            catch (hresult_error const &)
+            {
+                transparent = false;
+            }
+        }
+        // Fall back to an ordinary opaque window if either half failed.
+        if (opts.transparent && !transparent)
+            SetWindowLong(hwnd, GWL_EXSTYLE, GetWindowLong(hwnd, GWL_EXSTYLE) & ~WS_EX_LAYERED);
+
         SetWindowLongPtr(hwnd, GWLP_USERDATA, (LONG_PTR)this);
+        DragAcceptFiles(hwnd, TRUE);
 
         webview.AddInitializeScript(L"window.webview = function (s) { window.external.notify(s); };");
+        webview.AddInitializeScript(DRAG_SCRIPT);
         auto data = opts.data;
         auto message = opts.message;
         webview.ScriptNotify([=](auto const &, auto const &args) {
             std::string s = winrt::to_string(args.Value());
+            if (s == DRAG_MESSAGE)
+            {
+                begin_drag();
+                return;
+            }
             message(data, s.c_str());
         });
 
-        bool saved_fullscreen = false;
-        RECT saved_rect;
-        LONG saved_style = -1;
-        webview.ContainsFullScreenElementChanged([=](auto const &sender, auto const &) mutable {
-            bool fullscreen = sender.ContainsFullScreenElement();
-            if (fullscreen == saved_fullscreen)
+        auto dom_content_loaded = opts.dom_content_loaded;
+        webview.DOMContentLoaded([=](auto const &, auto const &) {
+            dom_content_loaded(data);
+        });
+
+        auto navigation_completed = opts.navigation_completed;
+        webview.NavigationCompleted([=](auto const &, auto const &args) {
+            auto uri = args.Uri();
+            std::string s = uri ? winrt::to_string(uri.AbsoluteUri()) : std::string();
+            navigation_completed(data, s.c_str(), args.IsSuccess());
+        });
+
+        // Keys pressed in the page go to the web view's own window, so
+        // shortcuts are filtered here rather than in our window procedure.
+        auto accelerator = opts.accelerator;
+        webview.AcceleratorKeyPressed([=](auto const &, auto const &args) {
+            auto type = args.EventType();
+            if (type != Windows::UI::Core::CoreAcceleratorKeyEventType::KeyDown &&
+                type != Windows::UI::Core::CoreAcceleratorKeyEventType::SystemKeyDown)
                 return;
-            saved_fullscreen = fullscreen;
+            bool ctrl = GetKeyState(VK_CONTROL) < 0;
+            bool alt = GetKeyState(VK_MENU) < 0;
+            bool shift = GetKeyState(VK_SHIFT) < 0;
+            if (accelerator(data, (unsigned int)args.VirtualKey(), ctrl, alt, shift))
+                args.Handled(true);
+        });
+
+        webview.ContainsFullScreenElementChanged([this](auto const &sender, auto const &) {
+            set_fullscreen(sender.ContainsFullScreenElement());
+        });
+
+        if (opts.url)
+            webview.Navigate(Uri(winrt::to_hstring(opts.url)));
+        else if (opts.html)
+            webview.NavigateToString(winrt::to_hstring(opts.html));
+        this->opts.url = nullptr;
+        this->opts.html = nullptr;
+
+        if (opts.icon)
+            set_icon(opts.icon, opts.icon_width, opts.icon_height);
+        this->opts.icon = nullptr;
+
+        if (opts.visible)
+        {
+            ShowWindow(hwnd, SW_SHOW);
+            UpdateWindow(hwnd);
+        }
+    }
+
+    ~_webview()
+    {
+        // A null result tells the caller the window closed first.
+        for (auto const &pending : pending_evals)
+        {
+            auto done = pending->done;
+            pending->done = nullptr;
+            done(pending->data, false, nullptr);
+        }
 
-            if (sender.ContainsFullScreenElement())
+        if (small_icon)
+            DestroyIcon(small_icon);
+        if (big_icon)
+            DestroyIcon(big_icon);
+    }
+
+    // The title bar and taskbar want different sizes, so each gets its own
+    // icon scaled from the source.
+    bool set_icon(const unsigned char *rgba, size_t width, size_t height)
+    {
+        HICON icon = icon_from_rgba(rgba, width, height);
+        if (!icon)
+            return false;
+
+        HICON small_new = (HICON)CopyImage(
+            icon, IMAGE_ICON, GetSystemMetrics(SM_CXSMICON), GetSystemMetrics(SM_CYSMICON), 0);
+        HICON big_new = (HICON)CopyImage(
+            icon, IMAGE_ICON, GetSystemMetrics(SM_CXICON), GetSystemMetrics(SM_CYICON), 0);
+        DestroyIcon(icon);
+        return set_icons(small_new, big_new);
+    }
+
+    bool set_icon_from_file(const char *path)
+    {
+        auto wide = winrt::to_hstring(path);
+        HICON small_new = (HICON)LoadImageW(
+            nullptr, wide.c_str(), IMAGE_ICON, GetSystemMetrics(SM_CXSMICON), GetSystemMetrics(SM_CYSMICON), LR_LOADFROMFILE);
+        HICON big_new = (HICON)LoadImageW(
+            nullptr, wide.c_str(), IMAGE_ICON, GetSystemMetrics(SM_CXICON), GetSystemMetrics(SM_CYICON), LR_LOADFROMFILE);
+        return set_icons(small_new, big_new);
+    }
+
+    // Takes ownership of both icons, keeping the current ones unless both are valid.
+    bool set_icons(HICON small_new, HICON big_new)
+    {
+        if (!small_new || !big_new)
+        {
+            if (small_new)
+                DestroyIcon(small_new);
+            if (big_new)
+                DestroyIcon(big_new);
+            return false;
+        }
+
+        SendMessage(hwnd, WM_SETICON, ICON_SMALL, (LPARAM)small_new);
+        SendMessage(hwnd, WM_SETICON, ICON_BIG, (LPARAM)big_new);
+        if (small_icon)
+            DestroyIcon(small_icon);
+        if (big_icon)
+            DestroyIcon(big_icon);
+        small_icon = small_new;
+        big_icon = big_new;
+        return true;
+    }
+
+    void drop_files(HDROP drop)
+    {
+        UINT count = DragQueryFileW(drop, 0xFFFFFFFF, nullptr, 0);
+        std::vector<std::string> paths;
+        for (UINT i = 0; i < count; i++)
+        {
+            std::wstring path(DragQueryFileW(drop, i, nullptr, 0) + 1, L'\0');
+            path.resize(DragQueryFileW(drop, i, &path[0], (UINT)path.size()));
+            paths.push_back(winrt::to_string(path));
+        }
+        DragFinish(drop);
+
+        std::vector<const char *> ptrs;
+        for (auto const &path : paths)
+            ptrs.push_back(path.c_str());
+        opts.file_drop(opts.data, ptrs.data(), ptrs.size());
+    }
+
+    void eval_async(const char *js, void *data, void (*done)(void *data, bool ok, const char *result))
+    {
+        auto pending = std::make_shared<PendingEval>(PendingEval{data, done});
+        pending_evals.push_back(pending);
+        auto operation = webview.InvokeScriptAsync(
+            L"eval",
+            single_threaded_vector<hstring>({winrt::to_hstring(js)}));
+        operation.Completed([this, pending](auto const &operation, auto) {
+            if (!pending->done)
+                return;
+            auto done = pending->done;
+            pending->done = nullptr;
+            pending_evals.erase(std::find(pending_evals.begin(), pending_evals.end(), pending));
+
+            try
             {
-                GetWindowRect(hwnd, &saved_rect);
-                saved_style = GetWindowLong(hwnd, GWL_STYLE);
-                SetWindowLong(hwnd, GWL_STYLE, saved_style & ~(WS_CAPTION | WS_THICKFRAME));
-                MONITORINFO mi;
-                mi.cbSize = sizeof mi;
-                GetMonitorInfo(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), &mi);
-                RECT screen_rect = mi.rcMonitor;
-                SetWindowPos(
-                    hwnd,
-                    HWND_TOP,
-                    screen_rect.left,
-                    screen_rect.top,
-                    screen_rect.right - screen_rect.left,
-                    screen_rect.bottom - screen_rect.top,
-                    SWP_FRAMECHANGED);
+                done(pending->data, true, winrt::to_string(operation.GetResults()).c_str());
             }
-            else
+            catch (hresult_error const &err)
             {
-                SetWindowLong(hwnd, GWL_STYLE, saved_style);
-                SetWindowPos(
-                    hwnd,
-                    HWND_TOP,
-                    saved_rect.left,
-                    saved_rect.top,
-                    saved_rect.right - saved_rect.left,
-                    saved_rect.bottom - saved_rect.top,
-                    SWP_FRAMECHANGED);
+                done(pending->data, false, winrt::to_string(err.message()).c_str());
             }
         });
+    }
+
+    // The page lives in another process, so mouse input never reaches our
+    // window procedure. Dragging starts from the page instead and we hand
+    // the rest over to the system's caption drag loop.
+    void begin_drag()
+    {
+        ReleaseCapture();
+        SendMessage(hwnd, WM_NCLBUTTONDOWN, HTCAPTION, 0);
+    }
 
-        ShowWindow(hwnd, SW_SHOW);
-        UpdateWindow(hwnd);
+    void set_fullscreen(bool value)
+    {
+        if (value == fullscreen)
+            return;
+        fullscreen = value;
+
+        if (fullscreen)
+        {
+            GetWindowRect(hwnd, &saved_rect);
+            saved_style = GetWindowLong(hwnd, GWL_STYLE);
+            SetWindowLong(hwnd, GWL_STYLE, saved_style & ~(WS_CAPTION | WS_THICKFRAME));
+            MONITORINFO mi;
+            mi.cbSize = sizeof mi;
+            GetMonitorInfo(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), &mi);
+            RECT screen_rect = mi.rcMonitor;
+            SetWindowPos(
+                hwnd,
+                HWND_TOP,
+                screen_rect.left,
+                screen_rect.top,
+                screen_rect.right - screen_rect.left,
+                screen_rect.bottom - screen_rect.top,
+                SWP_FRAMECHANGED);
+        }
+        else
+        {
+            SetWindowLong(hwnd, GWL_STYLE, saved_style);
+            SetWindowPos(
+                hwnd,
+                HWND_TOP,
+                saved_rect.left,
+                saved_rect.top,
+                saved_rect.right - saved_rect.left,
+                saved_rect.bottom - saved_rect.top,
+                SWP_FRAMECHANGED);
+        }
     }
 };
 
@@ -136,7 +431,14 @@ static LRESULT CALLBACK WndProc(HWND hwnd, UINT msg, WPARAM wParam, LPARAM lPara
     switch (msg)
     {
     case WM_CLOSE:
-        DestroyWindow(hwnd);
+        if (EXITING || ENDING_SESSION || window->opts.closing(window->opts.data))
+            DestroyWindow(hwnd);
+        break;
+    case WM_QUERYENDSESSION:
+        ENDING_SESSION = true;
+        return TRUE;
+    case WM_ENDSESSION:
+        ENDING_SESSION = wParam == TRUE;
         break;
     case WM_DESTROY:
         (window->opts.closed)(window->opts.data);
@@ -145,12 +447,30 @@ static LRESULT CALLBACK WndProc(HWND hwnd, UINT msg, WPARAM wParam, LPARAM lPara
     case WM_SIZE:
         window->webview.Bounds(getClientRect(hwnd));
         break;
+    case WM_DROPFILES:
+        window->drop_files((HDROP)wParam);
+        break;
+    case WM_ERASEBKGND:
+        if (window && window->transparent)
+        {
+            RECT rect;
+            GetClientRect(hwnd, &rect);
+            HBRUSH brush = CreateSolidBrush(TRANSPARENT_KEY);
+            FillRect((HDC)wParam, &rect, brush);
+            DeleteObject(brush);
+            return 1;
+        }
+        return DefWindowProc(hwnd, msg, wParam, lParam);
     case WM_GETMINMAXINFO:
         if (window)
         {
             LPMINMAXINFO lpMMI = (LPMINMAXINFO)lParam;
             lpMMI->ptMinTrackSize.x = window->opts.minimum_width;
             lpMMI->ptMinTrackSize.y = window->opts.minimum_height;
+            if (window->opts.maximum_width)
+                lpMMI->ptMaxTrackSize.x = window->opts.maximum_width;
+            if (window->opts.maximum_height)
+                lpMMI->ptMaxTrackSize.y = window->opts.maximum_height;
             break;
         }
     default:
@@ -204,29 +524,70 @@ void webview_start(void (*func)(void))
         {
         case WM_APP_DISPATCH:
             dispatch = (Dispatch *)msg.lParam;
-            dispatch->func(dispatch->data);
+            if (EXITING)
+                dispatch->cancel(dispatch->data);
+            else
+                dispatch->func(dispatch->data);
             delete dispatch;
             break;
         }
     }
+
+    // Free whatever is still queued without running it. The cancels run
+    // after the lock is released, as dropping a closure may dispatch again.
+    std::vector<Dispatch *> pending;
+    {
+        std::lock_guard<std::mutex> lock(DISPATCH_LOCK);
+        STOPPED = true;
+        while (PeekMessage(&msg, nullptr, WM_APP_DISPATCH, WM_APP_DISPATCH, PM_REMOVE))
+        {
+            if (!msg.hwnd)
+                pending.push_back((Dispatch *)msg.lParam);
+        }
+    }
+    for (Dispatch *dispatch : pending)
+    {
+        dispatch->cancel(dispatch->data);
+        delete dispatch;
+    }
 }
 
-void webview_dispatch(void *data, void (*func)(void *data))
+void webview_dispatch(void *data, void (*func)(void *data), void (*cancel)(void *data))
 {
-    PostThreadMessage(
-        MAIN_THREAD,
-        WM_APP_DISPATCH,
-        0,
-        (LPARAM) new Dispatch({data, func}));
+    Dispatch *dispatch = new Dispatch({data, func, cancel});
+    bool posted;
+    {
+        std::lock_guard<std::mutex> lock(DISPATCH_LOCK);
+        posted = !STOPPED && PostThreadMessage(MAIN_THREAD, WM_APP_DISPATCH, 0, (LPARAM)dispatch);
+    }
+    if (!posted)
+    {
+        cancel(data);
+        delete dispatch;
+    }
 }
 
 void webview_exit(void)
 {
+    EXITING = true;
     PostQuitMessage(0);
 }
 
 webview webview_new(webview_options opts)
 {
+    // Check the URL up front; throwing from the constructor would leave a
+    // half-built window behind.
+    if (opts.url)
+    {
+        try
+        {
+            Uri(winrt::to_hstring(opts.url));
+        }
+        catch (hresult_error const &)
+        {
+            return nullptr;
+        }
+    }
     return new _webview(opts);
 }
 
@@ -237,6 +598,38 @@ void webview_eval(webview self, const char *js)
         single_threaded_vector<hstring>({winrt::to_hstring(js)}));
 }
 
+void webview_add_init_script(webview self, const char *js)
+{
+    self->webview.AddInitializeScript(winrt::to_hstring(js));
+}
+
+bool webview_eval_result(webview self, const char *js, char **result)
+{
+    try
+    {
+        auto value = block(self->webview.InvokeScriptAsync(
+            L"eval",
+            single_threaded_vector<hstring>({winrt::to_hstring(js)})));
+        *result = _strdup(winrt::to_string(value).c_str());
+        return true;
+    }
+    catch (hresult_error const &err)
+    {
+        *result = _strdup(winrt::to_string(err.message()).c_str());
+        return false;
+    }
+}
+
+void webview_eval_async(webview self, const char *js, void *data, void (*done)(void *data, bool ok, const char *result))
+{
+    self->eval_async(js, data, done);
+}
+
+void webview_string_free(char *s)
+{
+    free(s);
+}
+
 void webview_load(webview self, const char *html)
 {
     self->webview.NavigateToString(winrt::to_hstring(html));
@@ -244,7 +637,270 @@ void webview_load(webview self, const char *html)
 
 void webview_title(webview self, const char *title)
 {
-    SetWindowText(self->hwnd, title);
+    SetWindowTextW(self->hwnd, winrt::to_hstring(title).c_str());
+}
+
+char *webview_get_title(webview self)
+{
+    int length = GetWindowTextLengthW(self->hwnd);
+    std::wstring title(length + 1, L'\0');
+    length = GetWindowTextW(self->hwnd, &title[0], length + 1);
+    title.resize(length);
+    return _strdup(winrt::to_string(title).c_str());
+}
+
+void *webview_get_hwnd(webview self)
+{
+    return self->hwnd;
+}
+
+double webview_zoom_factor(webview self)
+{
+    return self->webview.Scale();
+}
+
+void webview_set_zoom_factor(webview self, double factor)
+{
+    self->webview.Scale(factor);
+}
+
+void webview_reload(webview self, bool ignore_cache)
+{
+    // Refresh has no cache option, but EdgeHTML still honors the legacy
+    // forceGet argument to location.reload.
+    if (ignore_cache)
+        self->webview.InvokeScriptAsync(
+            L"eval",
+            single_threaded_vector<hstring>({L"location.reload(true)"}));
+    else
+        self->webview.Refresh();
+}
+
+void webview_stop(webview self)
+{
+    self->webview.Stop();
+}
+
+void webview_go_back(webview self)
+{
+    if (self->webview.CanGoBack())
+        self->webview.GoBack();
+}
+
+void webview_go_forward(webview self)
+{
+    if (self->webview.CanGoForward())
+        self->webview.GoForward();
+}
+
+bool webview_can_go_back(webview self)
+{
+    return self->webview.CanGoBack();
+}
+
+bool webview_can_go_forward(webview self)
+{
+    return self->webview.CanGoForward();
+}
+
+void webview_size(webview self, size_t *width, size_t *height)
+{
+    RECT rect;
+    GetClientRect(self->hwnd, &rect);
+    *width = rect.right - rect.left;
+    *height = rect.bottom - rect.top;
+}
+
+void webview_set_size(webview self, size_t width, size_t height)
+{
+    // `width` and `height` are the client size, but the limits apply to the
+    // whole window, as they do in WM_GETMINMAXINFO.
+    RECT rect = {0, 0, (LONG)width, (LONG)height};
+    AdjustWindowRectEx(
+        &rect,
+        GetWindowLong(self->hwnd, GWL_STYLE),
+        false,
+        GetWindowLong(self->hwnd, GWL_EXSTYLE));
+    size_t outer_width = rect.right - rect.left;
+    size_t outer_height = rect.bottom - rect.top;
+
+    outer_width = max(outer_width, self->opts.minimum_width);
+    outer_height = max(outer_height, self->opts.minimum_height);
+    if (self->opts.maximum_width)
+        outer_width = min(outer_width, self->opts.maximum_width);
+    if (self->opts.maximum_height)
+        outer_height = min(outer_height, self->opts.maximum_height);
+
+    SetWindowPos(
+        self->hwnd,
+        nullptr,
+        0,
+        0,
+        (int)outer_width,
+        (int)outer_height,
+        SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE);
+}
+
+void webview_position(webview self, int *x, int *y)
+{
+    RECT rect;
+    GetWindowRect(self->hwnd, &rect);
+    *x = rect.left;
+    *y = rect.top;
+}
+
+void webview_set_position(webview self, int x, int y)
+{
+    SetWindowPos(self->hwnd, nullptr, x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+}
+
+void webview_center_on_monitor(webview self)
+{
+    POINT cursor;
+    GetCursorPos(&cursor);
+    MONITORINFO mi;
+    mi.cbSize = sizeof mi;
+    GetMonitorInfo(MonitorFromPoint(cursor, MONITOR_DEFAULTTONEAREST), &mi);
+
+    RECT rect;
+    GetWindowRect(self->hwnd, &rect);
+    LONG width = rect.right - rect.left;
+    LONG height = rect.bottom - rect.top;
+    RECT work = mi.rcWork;
+    webview_set_position(
+        self,
+        work.left + (work.right - work.left - width) / 2,
+        work.top + (work.bottom - work.top - height) / 2);
+}
+
+// The bounds the window returns to when restored, in the same terms as
+// `webview_position` and `webview_size`, even while it is maximized or
+// minimized.
+void webview_restored_bounds(webview self, int *x, int *y, size_t *width, size_t *height, bool *maximized)
+{
+    WINDOWPLACEMENT placement;
+    placement.length = sizeof placement;
+    GetWindowPlacement(self->hwnd, &placement);
+
+    // The placement is in workspace coordinates, which exclude a taskbar at
+    // the top or left of the monitor.
+    MONITORINFO mi;
+    mi.cbSize = sizeof mi;
+    GetMonitorInfo(MonitorFromWindow(self->hwnd, MONITOR_DEFAULTTONEAREST), &mi);
+
+    RECT frame = {0, 0, 0, 0};
+    AdjustWindowRect(&frame, GetWindowLong(self->hwnd, GWL_STYLE), false);
+
+    RECT rect = placement.rcNormalPosition;
+    *x = rect.left + mi.rcWork.left - mi.rcMonitor.left;
+    *y = rect.top + mi.rcWork.top - mi.rcMonitor.top;
+    *width = (rect.right - rect.left) - (frame.right - frame.left);
+    *height = (rect.bottom - rect.top) - (frame.bottom - frame.top);
+    *maximized = placement.showCmd == SW_SHOWMAXIMIZED ||
+                 (placement.showCmd == SW_SHOWMINIMIZED && (placement.flags & WPF_RESTORETOMAXIMIZED));
+}
+
+// Resizing to the current size makes the window go through WM_GETMINMAXINFO
+// again, so it snaps into the new bounds right away.
+static void apply_size_constraints(webview self)
+{
+    RECT rect;
+    GetWindowRect(self->hwnd, &rect);
+    SetWindowPos(
+        self->hwnd,
+        nullptr,
+        0,
+        0,
+        rect.right - rect.left,
+        rect.bottom - rect.top,
+        SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE);
+}
+
+void webview_set_min_size(webview self, size_t width, size_t height)
+{
+    self->opts.minimum_width = width;
+    self->opts.minimum_height = height;
+    apply_size_constraints(self);
+}
+
+void webview_set_max_size(webview self, size_t width, size_t height)
+{
+    self->opts.maximum_width = width;
+    self->opts.maximum_height = height;
+    apply_size_constraints(self);
+}
+
+// EdgeHTML has no in-process developer tools. The DevTools Preview app runs
+// separately and attaches to debuggable web views on its own.
+void webview_open_dev_tools(webview self)
+{
+    if (!self->opts.debug)
+        return;
+
+    ShellExecute(
+        nullptr,
+        "open",
+        "shell:AppsFolder\\Microsoft.MicrosoftEdgeDevToolsPreview_8wekyb3d8bbwe!App",
+        nullptr,
+        nullptr,
+        SW_SHOWNORMAL);
+}
+
+void webview_show(webview self)
+{
+    ShowWindow(self->hwnd, SW_SHOW);
+}
+
+void webview_hide(webview self)
+{
+    ShowWindow(self->hwnd, SW_HIDE);
+}
+
+void webview_minimize(webview self)
+{
+    ShowWindow(self->hwnd, SW_MINIMIZE);
+}
+
+void webview_maximize(webview self)
+{
+    ShowWindow(self->hwnd, SW_MAXIMIZE);
+}
+
+void webview_restore(webview self)
+{
+    ShowWindow(self->hwnd, SW_RESTORE);
+}
+
+void webview_set_fullscreen(webview self, bool fullscreen)
+{
+    self->set_fullscreen(fullscreen);
+}
+
+void webview_set_always_on_top(webview self, bool on)
+{
+    SetWindowPos(
+        self->hwnd,
+        on ? HWND_TOPMOST : HWND_NOTOPMOST,
+        0,
+        0,
+        0,
+        0,
+        SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE);
+}
+
+bool webview_set_icon(webview self, const unsigned char *rgba, size_t width, size_t height)
+{
+    return self->set_icon(rgba, width, height);
+}
+
+bool webview_set_icon_from_file(webview self, const char *path)
+{
+    return self->set_icon_from_file(path);
+}
+
+void webview_begin_drag(webview self)
+{
+    self->begin_drag();
 }
 
 void webview_focus(webview self)
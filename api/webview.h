@@ -13,25 +13,85 @@ extern "C"
         size_t initial_width,
             initial_height,
             minimum_width,
-            minimum_height;
+            minimum_height,
+            maximum_width,
+            maximum_height;
+        bool has_position;
+        int x,
+            y;
+        bool center;
         bool borderless,
+            transparent,
+            always_on_top,
+            visible,
             debug;
+        const char *url;
+        const char *html;
+        const unsigned char *icon;
+        size_t icon_width,
+            icon_height;
         void *data;
         void (*message)(void *data, const char *message);
+        void (*dom_content_loaded)(void *data);
+        void (*navigation_completed)(void *data, const char *uri, bool ok);
+        bool (*accelerator)(void *data, unsigned int key, bool ctrl, bool alt, bool shift);
+        void (*file_drop)(void *data, const char *const *paths, size_t count);
+        bool (*closing)(void *data);
         void (*closed)(void *data);
     } webview_options;
 
     void webview_start(void (*func)(void));
 
-    void webview_dispatch(void *data, void (*func)(void *data));
+    void webview_dispatch(void *data, void (*func)(void *data), void (*cancel)(void *data));
     void webview_exit(void);
 
     webview webview_new(webview_options opts);
 
     void webview_eval(webview self, const char *js);
+    void webview_add_init_script(webview self, const char *js);
+    bool webview_eval_result(webview self, const char *js, char **result);
+    void webview_eval_async(webview self, const char *js, void *data, void (*done)(void *data, bool ok, const char *result));
     void webview_load(webview self, const char *html);
     void webview_title(webview self, const char *title);
+    char *webview_get_title(webview self);
+    void *webview_get_hwnd(webview self);
 
+    double webview_zoom_factor(webview self);
+    void webview_set_zoom_factor(webview self, double factor);
+
+    void webview_reload(webview self, bool ignore_cache);
+    void webview_stop(webview self);
+
+    void webview_go_back(webview self);
+    void webview_go_forward(webview self);
+    bool webview_can_go_back(webview self);
+    bool webview_can_go_forward(webview self);
+
+    void webview_size(webview self, size_t *width, size_t *height);
+    void webview_set_size(webview self, size_t width, size_t height);
+    void webview_set_min_size(webview self, size_t width, size_t height);
+    void webview_set_max_size(webview self, size_t width, size_t height);
+
+    void webview_position(webview self, int *x, int *y);
+    void webview_set_position(webview self, int x, int y);
+    void webview_center_on_monitor(webview self);
+    void webview_restored_bounds(webview self, int *x, int *y, size_t *width, size_t *height, bool *maximized);
+
+    void webview_string_free(char *s);
+
+    void webview_open_dev_tools(webview self);
+
+    void webview_show(webview self);
+    void webview_hide(webview self);
+    void webview_minimize(webview self);
+    void webview_maximize(webview self);
+    void webview_restore(webview self);
+    void webview_set_fullscreen(webview self, bool fullscreen);
+
+    void webview_set_always_on_top(webview self, bool on);
+    bool webview_set_icon(webview self, const unsigned char *rgba, size_t width, size_t height);
+    bool webview_set_icon_from_file(webview self, const char *path);
+    void webview_begin_drag(webview self);
     void webview_focus(webview self);
     void webview_close(webview self);
 
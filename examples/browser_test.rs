@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::path::Path;
 
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
@@ -37,9 +38,10 @@ fn main() {
         HwndType::FillWindow,
         None,
         None,
+        None,
         Some(move |control: Control| {
             println!("Control created!");
-            control.navigate_to_string(&url).unwrap();
+            control.navigate_to_file(Path::new(&url)).unwrap();
         }),
     )
     .unwrap();
@@ -62,6 +64,12 @@ fn main() {
                     .resize(None, Some((size.0 as i32, size.1 as i32)))
                     .unwrap();
             }
+            Event::WindowEvent {
+                event: WindowEvent::HiDpiFactorChanged(factor),
+                ..
+            } => {
+                control.handle_dpi_changed(factor).unwrap();
+            }
             _ => (),
         }
     });
@@ -39,16 +39,14 @@ fn main() {
             .unwrap();
     });
 
-    'running: loop {
-        for event in webview.poll_iter() {
-            match event {
-                Event::Quit => {
-                    break 'running;
-                }
-                _ => {}
-            }
+    webview.run(|webview, event| {
+        if let DOMContentLoaded = event {
+            webview
+                .inject_css("body { font-family: sans-serif; }")
+                .unwrap();
         }
-    }
+        ControlFlow::Continue
+    });
 
     worker.join().unwrap();
 }
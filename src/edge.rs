@@ -1,29 +1,66 @@
-use std::cell::RefCell;
-use std::fs::File;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString};
+use std::fs;
 use std::io;
-use std::io::prelude::*;
 use std::mem;
+use std::os::raw::c_char;
+use std::path::Path;
 use std::ptr;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "futures")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
 
-use winapi::shared::minwindef::{HINSTANCE, UINT};
-use winapi::shared::windef::{HWND, RECT};
+use winapi::shared::minwindef::{DWORD, FALSE, HINSTANCE, LPARAM, LRESULT, UINT, WPARAM};
+use winapi::shared::windef::{HBRUSH, HDC, HWND, POINT, RECT};
 use winapi::shared::winerror::{S_FALSE, S_OK};
-use winapi::um::winnt::LPCWSTR;
-use winapi::um::{libloaderapi, winuser};
+use winapi::um::combaseapi::{
+    CoWaitForMultipleHandles, COWAIT_DISPATCH_CALLS, COWAIT_DISPATCH_WINDOW_MESSAGES,
+    COWAIT_INPUTAVAILABLE,
+};
+use winapi::um::winbase::INFINITE;
+use winapi::um::winnt::{HANDLE, LPCWSTR};
+use winapi::um::{handleapi, libloaderapi, synchapi, wingdi, winuser};
 use winapi::winrt::roapi::{RoInitialize, RO_INIT_SINGLETHREADED};
 
+use winrt::windows::applicationmodel::datatransfer::DataPackage;
 use winrt::windows::foundation::{
-    metadata::ApiInformation, AsyncOperationCompletedHandler, EventRegistrationToken, Rect,
-    TypedEventHandler, Uri,
+    collections::IIterable, metadata::ApiInformation, AsyncActionCompletedHandler,
+    AsyncOperationCompletedHandler, DateTime, EventRegistrationToken, IAsyncAction,
+    IAsyncOperation, IReference, PropertyValue, Rect, TypedEventHandler, Uri,
+};
+use winrt::windows::security::cryptography::CryptographicBuffer;
+use winrt::windows::storage::streams::{
+    DataReader, IRandomAccessStream, InMemoryRandomAccessStream,
+};
+use winrt::windows::ui::Color as UiColor;
+use winrt::windows::web::http::{
+    filters::HttpBaseProtocolFilter, HttpBufferContent, HttpCookie, HttpCookieManager, HttpMethod,
+    HttpRequestMessage, HttpResponseMessage, HttpStatusCode, IHttpContent,
 };
 use winrt::windows::web::ui::{
-    interop::{IWebViewControlSite, WebViewControl, WebViewControlProcess},
-    IWebViewControl, WebViewControlScriptNotifyEventArgs,
+    interop::{
+        IWebViewControlSite, WebViewControl, WebViewControlMoveFocusReason, WebViewControlProcess,
+    },
+    IWebViewControl, WebViewControlContentLoadingEventArgs,
+    WebViewControlDeferredPermissionRequest, WebViewControlLongRunningScriptDetectedEventArgs,
+    WebViewControlNavigationCompletedEventArgs, WebViewControlNavigationStartingEventArgs,
+    WebViewControlNewWindowRequestedEventArgs, WebViewControlPermissionRequestedEventArgs,
+    WebViewControlPermissionType, WebViewControlScriptNotifyEventArgs, WebViewControlSettings,
+    WebViewControlUnsupportedUriSchemeIdentifiedEventArgs,
+    WebViewControlWebResourceRequestedEventArgs,
 };
-use winrt::{ComPtr, FastHString, RtDefaultConstructible};
+use winrt::windows::web::IUriToStreamResolver;
+use winrt::{ComPtr, FastHString, HString, RtDefaultConstructible, RtType};
 
+use crate::edge_manual::{ffi_result, js_string, set_range, Resource};
 use crate::error::Error;
+pub use crate::webview::WebView;
 
 struct FakeSend<T>(T);
 unsafe impl<T> Send for FakeSend<T> {}
@@ -33,8 +70,14 @@ unsafe impl Sync for HInstanceWrapper {}
 lazy_static! {
     static ref OUR_HINSTANCE: HInstanceWrapper =
         HInstanceWrapper(unsafe { libloaderapi::GetModuleHandleW(ptr::null()) });
+    static ref NOTIFY_CLASS_NAME: Vec<u16> = "WebViewControl Process Notify"
+        .encode_utf16()
+        .chain(Some(0))
+        .collect();
 }
 
+const WM_APP_PROCESS_EXITED: UINT = winuser::WM_APP;
+
 static HOST_CLASS_NAME: [u16; 20] = [
     b'W' as u16,
     b'e' as u16,
@@ -67,7 +110,7 @@ unsafe fn register_host_class() {
     winuser::RegisterClassExW(&winuser::WNDCLASSEXW {
         cbSize: mem::size_of::<winuser::WNDCLASSEXW>() as UINT,
         style: winuser::CS_HREDRAW | winuser::CS_VREDRAW | winuser::CS_OWNDC,
-        lpfnWndProc: Some(winuser::DefWindowProcW),
+        lpfnWndProc: Some(host_wndproc),
         cbClsExtra: 0,
         cbWndExtra: 0,
         hInstance: OUR_HINSTANCE.0,
@@ -80,17 +123,112 @@ unsafe fn register_host_class() {
     });
 }
 
-fn new_hwnd(parent: HWND, position: (i32, i32), size: (i32, i32)) -> Result<HWND, Error> {
+// Paints the host window with the brush from `HostWindowStyle::background`,
+// kept in `GWLP_USERDATA`, until the control covers it.
+unsafe extern "system" fn host_wndproc(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let brush = winuser::GetWindowLongPtrW(hwnd, winuser::GWLP_USERDATA) as HBRUSH;
+    match msg {
+        winuser::WM_ERASEBKGND if !brush.is_null() => {
+            let mut rect = mem::zeroed();
+            winuser::GetClientRect(hwnd, &mut rect);
+            winuser::FillRect(wparam as HDC, &rect, brush);
+            return 1;
+        }
+        winuser::WM_NCDESTROY if !brush.is_null() => {
+            winuser::SetWindowLongPtrW(hwnd, winuser::GWLP_USERDATA, 0);
+            wingdi::DeleteObject(brush as _);
+        }
+        _ => {}
+    }
+    winuser::DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+unsafe fn register_notify_class() {
+    winuser::RegisterClassExW(&winuser::WNDCLASSEXW {
+        cbSize: mem::size_of::<winuser::WNDCLASSEXW>() as UINT,
+        style: 0,
+        lpfnWndProc: Some(notify_wndproc),
+        cbClsExtra: 0,
+        cbWndExtra: 0,
+        hInstance: OUR_HINSTANCE.0,
+        hIcon: ptr::null_mut(),
+        hCursor: ptr::null_mut(),
+        hbrBackground: ptr::null_mut(),
+        lpszMenuName: ptr::null(),
+        lpszClassName: NOTIFY_CLASS_NAME.as_ptr(),
+        hIconSm: ptr::null_mut(),
+    });
+}
+
+unsafe extern "system" fn notify_wndproc(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_APP_PROCESS_EXITED {
+        let notifier =
+            winuser::GetWindowLongPtrW(hwnd, winuser::GWLP_USERDATA) as *const ExitNotifier;
+        if let Some(notifier) = notifier.as_ref() {
+            notifier.notify();
+        }
+        return 0;
+    }
+    winuser::DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+// A message-only window used to get process notifications back onto the UI
+// thread, whichever thread WinRT raises them on.
+fn new_notify_hwnd() -> Result<HWND, Error> {
     unsafe {
-        register_host_class();
+        register_notify_class();
     }
 
     let handle = unsafe {
         winuser::CreateWindowExW(
             0,
+            NOTIFY_CLASS_NAME.as_ptr(),
+            [0].as_ptr() as LPCWSTR,
+            0,
+            0,
+            0,
+            0,
+            0,
+            winuser::HWND_MESSAGE,
+            ptr::null_mut(),
+            OUR_HINSTANCE.0,
+            ptr::null_mut(),
+        )
+    };
+
+    if handle.is_null() {
+        return Err(Error::Io(io::Error::last_os_error()));
+    }
+
+    Ok(handle)
+}
+
+fn new_hwnd(
+    parent: HWND,
+    position: (i32, i32),
+    size: (i32, i32),
+    style: HostWindowStyle,
+) -> Result<HWND, Error> {
+    unsafe {
+        register_host_class();
+    }
+
+    let handle = unsafe {
+        winuser::CreateWindowExW(
+            style.ex_style(),
             HOST_CLASS_NAME.as_ptr(),
             [0].as_ptr() as LPCWSTR,
-            winuser::WS_CHILD | winuser::WS_VISIBLE,
+            style.style(),
             position.0,
             position.1,
             size.0,
@@ -106,9 +244,435 @@ fn new_hwnd(parent: HWND, position: (i32, i32), size: (i32, i32)) -> Result<HWND
         return Err(Error::Io(io::Error::last_os_error()));
     }
 
+    if let Some(Color(r, g, b)) = style.background {
+        unsafe {
+            let brush = wingdi::CreateSolidBrush(wingdi::RGB(r, g, b));
+            winuser::SetWindowLongPtrW(handle, winuser::GWLP_USERDATA, brush as _);
+        }
+    }
+
     Ok(handle)
 }
 
+// Waits for the handler installed by `register` to signal while still pumping
+// window messages, since WebViewControl operations complete on the UI thread
+// that would otherwise be blocked.
+fn block_on<R>(register: R) -> Result<(), Error>
+where
+    R: FnOnce(FakeSend<HANDLE>) -> Result<(), winrt::Error>,
+{
+    let event = unsafe { synchapi::CreateEventW(ptr::null_mut(), FALSE, FALSE, ptr::null()) };
+    if event.is_null() {
+        return Err(Error::Io(io::Error::last_os_error()));
+    }
+
+    let result = register(FakeSend(event))
+        .map_err(Error::from)
+        .and_then(|()| {
+            let mut handles = [event];
+            let mut index = 0;
+            let hr = unsafe {
+                CoWaitForMultipleHandles(
+                    COWAIT_DISPATCH_WINDOW_MESSAGES | COWAIT_DISPATCH_CALLS | COWAIT_INPUTAVAILABLE,
+                    INFINITE,
+                    1,
+                    handles.as_mut_ptr(),
+                    &mut index,
+                )
+            };
+            if hr != S_OK {
+                return Err(Error::Io(io::Error::from_raw_os_error(hr)));
+            }
+            Ok(())
+        });
+
+    unsafe { handleapi::CloseHandle(event) };
+    result
+}
+
+fn wait_for<T: RtType + 'static>(operation: &IAsyncOperation<T>) -> Result<T::Out, Error> {
+    block_on(|signal| {
+        operation.set_completed(&AsyncOperationCompletedHandler::new(
+            move |_sender, _status| {
+                unsafe { synchapi::SetEvent(signal.0) };
+                Ok(())
+            },
+        ))
+    })?;
+    Ok(operation.get_results()?)
+}
+
+fn wait_for_action(action: &IAsyncAction) -> Result<(), Error> {
+    block_on(|signal| {
+        action.set_completed(&AsyncActionCompletedHandler::new(
+            move |_sender, _status| {
+                unsafe { synchapi::SetEvent(signal.0) };
+                Ok(())
+            },
+        ))
+    })?;
+    Ok(action.get_results()?)
+}
+
+fn read_stream(stream: &IRandomAccessStream) -> Result<Vec<u8>, Error> {
+    let size = stream.get_size()? as u32;
+    let input = stream.get_input_stream_at(0)?.unwrap();
+    let reader = DataReader::create_data_reader(&input)?.unwrap();
+    wait_for(&reader.load_async(size)?.unwrap())?;
+    let mut bytes = vec![0; size as usize];
+    reader.read_bytes(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Resolves with the result of a WinRT async operation. Operations complete on
+/// the UI thread, so the executor polling this has to run there too and pump
+/// window messages while it waits.
+#[cfg(feature = "futures")]
+pub struct EdgeFuture<T> {
+    state: Rc<RefCell<FutureState<T>>>,
+}
+
+#[cfg(feature = "futures")]
+struct FutureState<T> {
+    result: Option<Result<T, Error>>,
+    waker: Option<Waker>,
+}
+
+#[cfg(feature = "futures")]
+impl<T> EdgeFuture<T> {
+    fn new() -> (EdgeFuture<T>, impl FnOnce(Result<T, Error>)) {
+        let state = Rc::new(RefCell::new(FutureState {
+            result: None,
+            waker: None,
+        }));
+        let complete_state = state.clone();
+        let complete = move |result| {
+            let waker = {
+                let mut state = complete_state.borrow_mut();
+                state.result = Some(result);
+                state.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        };
+        (EdgeFuture { state }, complete)
+    }
+
+    fn ready(result: Result<T, Error>) -> EdgeFuture<T> {
+        let (future, complete) = EdgeFuture::new();
+        complete(result);
+        future
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T> Future for EdgeFuture<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.borrow_mut();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+// Resolves with the operation's results passed through `convert`.
+#[cfg(feature = "futures")]
+fn operation_future<T, U, C>(operation: &IAsyncOperation<T>, convert: C) -> EdgeFuture<U>
+where
+    T: RtType + 'static,
+    U: 'static,
+    C: FnOnce(T::Out) -> U + 'static,
+{
+    let (future, complete) = EdgeFuture::new();
+    let mut complete = FakeSend(Some((complete, convert)));
+    let registered = operation.set_completed(&AsyncOperationCompletedHandler::new(
+        move |sender, _status| {
+            if let Some((complete, convert)) = complete.0.take() {
+                let result = unsafe { &*sender }.get_results();
+                complete(result.map(convert).map_err(Error::from));
+            }
+            Ok(())
+        },
+    ));
+    match registered {
+        Ok(()) => future,
+        Err(err) => EdgeFuture::ready(Err(err.into())),
+    }
+}
+
+// winrt-rs can't implement collection interfaces itself, so borrow the string
+// vector that backs a data package's file types.
+fn hstring_iterable(items: &[&str]) -> Result<ComPtr<IIterable<HString>>, Error> {
+    let package = DataPackage::new();
+    let vector = package
+        .get_properties()?
+        .unwrap()
+        .get_file_types()?
+        .unwrap();
+    for item in items {
+        vector.append(&FastHString::from(*item))?;
+    }
+    Ok(vector.query_interface::<IIterable<HString>>().unwrap())
+}
+
+fn uri_to_string(uri: Option<ComPtr<Uri>>) -> Result<String, winrt::Error> {
+    match uri {
+        Some(uri) => Ok(uri.get_absolute_uri()?.to_string()),
+        None => Ok(String::new()),
+    }
+}
+
+fn physical_to_dips(rect: RECT, scale: f64) -> Rect {
+    let scale = scale as f32;
+    Rect {
+        X: rect.left as f32 / scale,
+        Y: rect.top as f32 / scale,
+        Width: (rect.right - rect.left) as f32 / scale,
+        Height: (rect.bottom - rect.top) as f32 / scale,
+    }
+}
+
+fn source(control: &WebViewControl) -> Option<String> {
+    match control.get_source() {
+        Ok(Some(uri)) => uri.get_absolute_uri().ok().map(|uri| uri.to_string()),
+        _ => None,
+    }
+}
+
+/// The largest payload `Control::navigate_to_data_uri` accepts.
+pub const MAX_DATA_URI_BYTES: usize = 2 * 1024 * 1024;
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let n = ((chunk[0] as u32) << 16)
+            | ((*chunk.get(1).unwrap_or(&0) as u32) << 8)
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// `DateTime` counts 100ns ticks since 1601-01-01.
+const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+
+fn to_date_time(time: SystemTime) -> DateTime {
+    let ticks = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_nanos() / 100) as i64,
+        Err(err) => -((err.duration().as_nanos() / 100) as i64),
+    };
+    DateTime {
+        UniversalTime: UNIX_EPOCH_TICKS + ticks,
+    }
+}
+
+fn from_date_time(time: DateTime) -> SystemTime {
+    let ticks = time.UniversalTime - UNIX_EPOCH_TICKS;
+    if ticks >= 0 {
+        UNIX_EPOCH + Duration::from_nanos(ticks as u64 * 100)
+    } else {
+        UNIX_EPOCH - Duration::from_nanos(-ticks as u64 * 100)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// `None` for a session cookie.
+    pub expiry: Option<SystemTime>,
+    pub http_only: bool,
+    pub secure: bool,
+}
+
+impl Cookie {
+    fn from_http_cookie(cookie: &HttpCookie) -> Result<Cookie, Error> {
+        let expiry = match cookie.get_expires()? {
+            Some(expires) => Some(from_date_time(expires.get_value()?)),
+            None => None,
+        };
+        Ok(Cookie {
+            name: cookie.get_name()?.to_string(),
+            value: cookie.get_value()?.to_string(),
+            domain: cookie.get_domain()?.to_string(),
+            path: cookie.get_path()?.to_string(),
+            expiry,
+            http_only: cookie.get_http_only()?,
+            secure: cookie.get_secure()?,
+        })
+    }
+
+    fn to_http_cookie(&self) -> Result<ComPtr<HttpCookie>, Error> {
+        let cookie = HttpCookie::create(
+            &FastHString::from(&*self.name),
+            &FastHString::from(&*self.domain),
+            &FastHString::from(&*self.path),
+        )?;
+        cookie.set_value(&FastHString::from(&*self.value))?;
+        if let Some(expiry) = self.expiry {
+            let expires = PropertyValue::create_date_time(to_date_time(expiry))?
+                .unwrap()
+                .query_interface::<IReference<DateTime>>()
+                .unwrap();
+            cookie.set_expires(&expires)?;
+        }
+        cookie.set_http_only(self.http_only)?;
+        cookie.set_secure(self.secure)?;
+        Ok(cookie)
+    }
+}
+
+// Web view controls share the WinINet cookie store with the rest of the
+// process, which is what the HTTP filter's cookie manager reads and writes.
+fn cookie_manager() -> Result<ComPtr<HttpCookieManager>, Error> {
+    Ok(HttpBaseProtocolFilter::new().get_cookie_manager()?.unwrap())
+}
+
+/// Serves the content for `ms-local-stream://` navigations.
+pub struct StreamResolver {
+    resolve: Box<dyn FnMut(&str) -> Option<Resource>>,
+    response: Option<(String, Vec<u8>, CString)>,
+}
+
+impl StreamResolver {
+    /// `f` is given the path of each request, starting with `/`, and returns
+    /// `None` for anything it doesn't have. It is always called on the thread
+    /// that created the control, and freed there too.
+    pub fn new<F: FnMut(&str) -> Option<Resource> + 'static>(f: F) -> StreamResolver {
+        StreamResolver {
+            resolve: Box::new(f),
+            response: None,
+        }
+    }
+
+    // winrt-rs can't implement interfaces, so the resolver object itself lives
+    // in webview-sys and calls back into us.
+    fn into_com(self) -> Result<ComPtr<IUriToStreamResolver>, Error> {
+        let context = Box::into_raw(Box::new(self));
+        let mut resolver = ptr::null_mut();
+        let hr = unsafe {
+            webview_sys::webview_new_stream_resolver(
+                context as *mut c_void,
+                Some(resolve_stream),
+                Some(free_stream_resolver),
+                &mut resolver,
+            )
+        };
+        if let Err(err) = ffi_result(((), hr)) {
+            unsafe { free_stream_resolver(context as *mut c_void) };
+            return Err(err);
+        }
+        Ok(unsafe { ComPtr::wrap(resolver as *mut IUriToStreamResolver) })
+    }
+}
+
+unsafe extern "C" fn resolve_stream(
+    context: *mut c_void,
+    path: *const c_char,
+    offset: u64,
+    max_length: u64,
+    content: *mut *const u8,
+    length: *mut usize,
+    total_length: *mut u64,
+    mime: *mut *const c_char,
+) -> bool {
+    let resolver = &mut *(context as *mut StreamResolver);
+    let path = CStr::from_ptr(path).to_string_lossy();
+    // A new stream is opened with a `max_length` of zero; its range reads
+    // reuse the response instead of resolving the path again.
+    let cached = match resolver.response {
+        Some((ref cached_path, _, _)) => max_length > 0 && *cached_path == path,
+        None => false,
+    };
+    if !cached {
+        resolver.response = (resolver.resolve)(&path).map(|resource| {
+            let resource_mime = CString::new(resource.mime).unwrap_or_default();
+            (path.into_owned(), resource.data, resource_mime)
+        });
+    }
+    match resolver.response {
+        Some((_, ref body, ref resource_mime)) => {
+            set_range(body, offset, max_length, content, length, total_length);
+            *mime = resource_mime.as_ptr();
+            true
+        }
+        None => false,
+    }
+}
+
+unsafe extern "C" fn free_stream_resolver(context: *mut c_void) {
+    drop(Box::from_raw(context as *mut StreamResolver));
+}
+
+fn navigate_to_local_stream_uri(
+    control: &WebViewControl,
+    relative_path: &str,
+    resolver: StreamResolver,
+) -> Result<(), Error> {
+    let uri = control
+        .build_local_stream_uri(
+            &FastHString::from("WebView"),
+            &FastHString::from(relative_path),
+        )?
+        .unwrap();
+    control.navigate_to_local_stream_uri(&uri, &resolver.into_com()?)?;
+    Ok(())
+}
+
+// Sends `options`, and `user_agent` if given, as request headers.
+fn navigate_with_options(
+    control: &WebViewControl,
+    url: &str,
+    options: &NavigateOptions,
+    user_agent: Option<&str>,
+) -> Result<(), Error> {
+    let uri = Uri::create_uri(&FastHString::from(url))?;
+    let mut headers = Vec::new();
+    if let Some(user_agent) = user_agent {
+        headers.push(("User-Agent", user_agent));
+    }
+    if let Some(ref referrer) = options.referrer {
+        headers.push(("Referer", referrer.as_str()));
+    }
+    match options.cache_mode {
+        CacheMode::Default => {}
+        CacheMode::NoCache => {
+            headers.push(("Cache-Control", "no-cache"));
+            headers.push(("Pragma", "no-cache"));
+        }
+        CacheMode::OnlyCache => headers.push(("Cache-Control", "only-if-cached")),
+    }
+
+    if headers.is_empty() {
+        control.navigate(&*uri)?;
+    } else {
+        let method = HttpMethod::get_get()?.unwrap();
+        let request = HttpRequestMessage::create(&method, &uri)?;
+        let request_headers = request.get_headers()?.unwrap();
+        for (name, value) in headers {
+            request_headers.append(&FastHString::from(name), &FastHString::from(value))?;
+        }
+        control.navigate_with_http_request_message(&request)?;
+    }
+    Ok(())
+}
+
 pub fn runtime_context() {
     let hr = unsafe { RoInitialize(RO_INIT_SINGLETHREADED) };
     assert!(
@@ -126,22 +690,152 @@ pub enum HwndType {
     NewHwndInWindow(HWND),
 }
 
+/// Styles for the host window created for `HwndType::NewHwndInWindow`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HostWindowStyle {
+    /// Sets `WS_CLIPCHILDREN`.
+    pub clip_children: bool,
+    /// Sets `WS_EX_TRANSPARENT`.
+    pub transparent: bool,
+    /// Creates the window with `WS_DISABLED`, so it ignores input until
+    /// enabled.
+    pub disabled: bool,
+    /// Fills the window with this color until the page paints, instead of
+    /// leaving it unpainted.
+    pub background: Option<Color>,
+}
+
+/// An opaque RGB color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl HostWindowStyle {
+    fn style(&self) -> DWORD {
+        let mut style = winuser::WS_CHILD | winuser::WS_VISIBLE;
+        if self.clip_children {
+            style |= winuser::WS_CLIPCHILDREN;
+        }
+        if self.disabled {
+            style |= winuser::WS_DISABLED;
+        }
+        style
+    }
+
+    fn ex_style(&self) -> DWORD {
+        if self.transparent {
+            winuser::WS_EX_TRANSPARENT
+        } else {
+            0
+        }
+    }
+}
+
+struct ExitNotifier {
+    hwnd: HWND,
+    exited: Cell<bool>,
+    handlers: RefCell<Vec<Box<dyn FnMut()>>>,
+}
+
+impl ExitNotifier {
+    fn notify(&self) {
+        self.exited.set(true);
+        let mut handlers = mem::replace(&mut *self.handlers.borrow_mut(), Vec::new());
+        #[cfg(feature = "log")]
+        {
+            if handlers.is_empty() {
+                log::warn!("WebViewControlProcess exited and no handler is registered");
+            }
+        }
+        for handler in handlers.iter_mut() {
+            handler();
+        }
+
+        // Keep any handlers that were added while notifying.
+        let mut current = self.handlers.borrow_mut();
+        handlers.append(&mut *current);
+        *current = handlers;
+    }
+}
+
+impl Drop for ExitNotifier {
+    fn drop(&mut self) {
+        unsafe { winuser::DestroyWindow(self.hwnd) };
+    }
+}
+
+/// Hosts the web view controls created from it.
+///
+/// Unlike the UWP `WebView`, `WebViewControlProcess` has no notion of a
+/// storage partition: every process shares the user's cookies, cache and
+/// local storage, so separate processes do not give isolated sessions.
+///
+/// Each process is a separate set of OS processes, so an application with
+/// many windows should create one `Process` and clone it for every window.
+/// Clones share the same process; once `control_count` drops to zero it can
+/// be terminated without affecting any window.
 #[derive(Clone)]
 pub struct Process {
     process: ComPtr<WebViewControlProcess>,
+    exit_notifier: Rc<ExitNotifier>,
+    controls: Rc<RefCell<Vec<Weak<RefCell<ControlInner>>>>>,
 }
 
 impl Process {
     pub fn new() -> Process {
         let process = WebViewControlProcess::new();
+
+        let exit_notifier = Rc::new(ExitNotifier {
+            hwnd: new_notify_hwnd().unwrap(),
+            exited: Cell::new(false),
+            handlers: RefCell::new(Vec::new()),
+        });
+        unsafe {
+            winuser::SetWindowLongPtrW(
+                exit_notifier.hwnd,
+                winuser::GWLP_USERDATA,
+                &*exit_notifier as *const ExitNotifier as isize,
+            );
+        }
+
+        let hwnd = FakeSend(exit_notifier.hwnd);
         process
             .add_process_exited(&TypedEventHandler::new(move |_proc, _result| {
-                eprintln!("WebViewControlProcess exited, should we do anything about it?");
+                unsafe { winuser::PostMessageW(hwnd.0, WM_APP_PROCESS_EXITED, 0, 0) };
                 Ok(())
             }))
             .unwrap();
 
-        Process { process }
+        Process {
+            process,
+            exit_notifier,
+            controls: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    pub fn terminate(&self) -> Result<(), Error> {
+        self.process.terminate()?;
+        self.exit_notifier.exited.set(true);
+        Ok(())
+    }
+
+    /// Whether the web view process is still up; false once it was
+    /// terminated or has exited on its own.
+    pub fn is_running(&self) -> bool {
+        !self.exit_notifier.exited.get()
+    }
+
+    /// The number of controls created from this process, through any clone,
+    /// that are still alive.
+    pub fn control_count(&self) -> usize {
+        let mut controls = self.controls.borrow_mut();
+        controls.retain(|control| control.upgrade().is_some());
+        controls.len()
+    }
+
+    /// Registers `f` to run on the UI thread when the web view process exits,
+    /// for example after a crash, so controls can be recreated.
+    pub fn add_process_exited<F: FnMut() + 'static>(&self, f: F) {
+        self.exit_notifier.handlers.borrow_mut().push(Box::new(f));
     }
 
     pub fn create_control(
@@ -149,12 +843,13 @@ impl Process {
         hwnd_type: HwndType,
         position: (i32, i32),
         size: (i32, i32),
+        style: HostWindowStyle,
         callback: Option<impl FnOnce(Control) + 'static>,
     ) -> Result<Control, Error> {
         let hwnd = match hwnd_type {
             HwndType::FillWindow(hwnd) => hwnd,
             HwndType::ConsumeHwnd(hwnd) => hwnd,
-            HwndType::NewHwndInWindow(parent) => new_hwnd(parent, position, size)?,
+            HwndType::NewHwndInWindow(parent) => new_hwnd(parent, position, size, style)?,
         };
 
         let operation = self.process.create_web_view_control_async(
@@ -176,8 +871,18 @@ impl Process {
                 },
                 control: None,
                 queued_bounds_update: None,
+                zoom_factor: 1.0,
+                dpi_factor: 1.0,
+                visible: true,
+                user_agent: None,
+                initialization_scripts: Vec::new(),
+                deferred_permissions: Rc::new(RefCell::new(Vec::new())),
+                event_tokens: Vec::new(),
             })),
         };
+        self.controls
+            .borrow_mut()
+            .push(Rc::downgrade(&control.inner));
 
         let mut control2 = FakeSend(control.clone());
         let mut callback = FakeSend(callback);
@@ -196,6 +901,24 @@ impl Process {
 
         Ok(control)
     }
+
+    /// Like `create_control`, but resolves with the control once it has been
+    /// created instead of taking a callback.
+    #[cfg(feature = "futures")]
+    pub fn create_control_future(
+        &self,
+        hwnd_type: HwndType,
+        position: (i32, i32),
+        size: (i32, i32),
+        style: HostWindowStyle,
+    ) -> EdgeFuture<Control> {
+        let (future, complete) = EdgeFuture::new();
+        let callback = move |control: Control| complete(Ok(control));
+        match self.create_control(hwnd_type, position, size, style, Some(callback)) {
+            Ok(_) => future,
+            Err(err) => EdgeFuture::ready(Err(err)),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -203,70 +926,638 @@ pub struct Control {
     inner: Rc<RefCell<ControlInner>>,
 }
 
-pub struct ControlInner {
-    hwnd: HWND,
-    is_window_hwnd: bool,
+/// Keeps a handler from `Control::on_script_notify` registered until dropped.
+pub struct ScriptNotifySubscription {
+    inner: Weak<RefCell<ControlInner>>,
+    token: EventRegistrationToken,
+}
 
-    control: Option<ComPtr<WebViewControl>>,
+impl Drop for ScriptNotifySubscription {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.upgrade() {
+            let _ = Control { inner }.remove_event(self.token);
+        }
+    }
+}
 
-    queued_bounds_update: Option<Rect>,
+/// The settings of a created control. Change them from the callback passed to
+/// `create_control` for them to apply to the first navigation.
+pub struct Settings {
+    settings: ComPtr<WebViewControlSettings>,
 }
 
-impl ControlInner {
-    fn update_bounds(&mut self) -> Result<(), Error> {
-        let mut rect = RECT {
-            top: 0,
-            left: 0,
-            bottom: 0,
-            right: 0,
-        };
-        if unsafe { winuser::GetWindowRect(self.hwnd, &mut rect) } == 0 {
-            return Err(Error::Io(io::Error::last_os_error()));
-        }
-        self.update_bounds_from_rect(Rect {
-            X: if self.is_window_hwnd {
-                0.0
-            } else {
-                rect.left as f32
-            },
-            Y: if self.is_window_hwnd {
-                0.0
-            } else {
-                rect.top as f32
-            },
-            Width: (rect.right - rect.left) as f32,
-            Height: (rect.bottom - rect.top) as f32,
-        })
+impl Settings {
+    pub fn is_javascript_enabled(&self) -> Result<bool, Error> {
+        Ok(self.settings.get_is_java_script_enabled()?)
     }
 
-    fn update_bounds_from_rect(&mut self, rect: Rect) -> Result<(), Error> {
-        println!("Updating bounds to {:?}", rect);
-        if let Some(ref control) = self.control {
-            let control_site = control.query_interface::<IWebViewControlSite>().unwrap();
-            control_site.set_bounds(rect)?;
-        } else {
-            self.queued_bounds_update = Some(rect);
-        }
-        Ok(())
+    pub fn set_is_javascript_enabled(&self, enabled: bool) -> Result<(), Error> {
+        Ok(self.settings.set_is_java_script_enabled(enabled)?)
     }
-}
 
-impl Control {
-    fn control_created(&mut self, web_view_control: Option<ComPtr<WebViewControl>>) {
-        let mut inner = self.inner.borrow_mut();
-        inner.control = web_view_control;
-        if let Some(rect) = inner.queued_bounds_update {
-            inner.queued_bounds_update = None;
-            let _ = inner.update_bounds_from_rect(rect);
-        }
+    pub fn is_indexed_db_enabled(&self) -> Result<bool, Error> {
+        Ok(self.settings.get_is_indexed_db_enabled()?)
     }
 
-    pub fn resize(
-        &self,
-        position: Option<(i32, i32)>,
-        size: Option<(i32, i32)>,
-    ) -> Result<(), Error> {
-        let mut inner = self.inner.borrow_mut();
+    pub fn set_is_indexed_db_enabled(&self, enabled: bool) -> Result<(), Error> {
+        Ok(self.settings.set_is_indexed_db_enabled(enabled)?)
+    }
+
+    pub fn is_script_notify_allowed(&self) -> Result<bool, Error> {
+        Ok(self.settings.get_is_script_notify_allowed()?)
+    }
+
+    /// Controls whether pages can call `window.external.notify`.
+    pub fn set_is_script_notify_allowed(&self, allowed: bool) -> Result<(), Error> {
+        Ok(self.settings.set_is_script_notify_allowed(allowed)?)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusDirection {
+    /// Focus moves into the control as if by tabbing forward into it.
+    Next,
+    /// Focus moves into the control as if by tabbing backward into it.
+    Previous,
+    /// Focus moves into the control without tab navigation semantics.
+    Programmatic,
+}
+
+impl FocusDirection {
+    fn to_rt(self) -> WebViewControlMoveFocusReason {
+        match self {
+            FocusDirection::Next => WebViewControlMoveFocusReason::Next,
+            FocusDirection::Previous => WebViewControlMoveFocusReason::Previous,
+            FocusDirection::Programmatic => WebViewControlMoveFocusReason::Programmatic,
+        }
+    }
+}
+
+fn move_focus(control: &WebViewControl, direction: FocusDirection) -> Result<(), Error> {
+    let control_site = control.query_interface::<IWebViewControlSite>().unwrap();
+    control_site.move_focus(direction.to_rt())?;
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PermissionKind {
+    Geolocation,
+    UnlimitedIndexedDbQuota,
+    Media,
+    PointerLock,
+    WebNotifications,
+    Screen,
+    Other,
+}
+
+impl PermissionKind {
+    fn from_rt(kind: WebViewControlPermissionType) -> PermissionKind {
+        match kind {
+            WebViewControlPermissionType::Geolocation => PermissionKind::Geolocation,
+            WebViewControlPermissionType::UnlimitedIndexedDBQuota => {
+                PermissionKind::UnlimitedIndexedDbQuota
+            }
+            WebViewControlPermissionType::Media => PermissionKind::Media,
+            WebViewControlPermissionType::PointerLock => PermissionKind::PointerLock,
+            WebViewControlPermissionType::WebNotifications => PermissionKind::WebNotifications,
+            WebViewControlPermissionType::Screen => PermissionKind::Screen,
+            _ => PermissionKind::Other,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PermissionDecision {
+    Allow,
+    Deny,
+    /// Leaves the page waiting until `Control::resolve_deferred_permission` or
+    /// the request from `Control::get_deferred_permission_request_by_id` is
+    /// answered.
+    Defer,
+}
+
+/// How `Control::navigate_with_options` uses the HTTP cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheMode {
+    Default,
+    /// Revalidates with the server, for pages that change often.
+    NoCache,
+    /// Asks for a cached response only. Servers and caches may ignore this.
+    OnlyCache,
+}
+
+impl Default for CacheMode {
+    fn default() -> CacheMode {
+        CacheMode::Default
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NavigateOptions {
+    /// Sent as the `Referer` header.
+    pub referrer: Option<String>,
+    pub cache_mode: CacheMode,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    pub match_case: bool,
+    pub whole_word: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FindResult {
+    pub count: usize,
+    /// The index of the highlighted match, or `None` if nothing matched.
+    pub active: Option<usize>,
+}
+
+const WEB_MESSAGE_PREFIX: &str = "__webview_message:";
+
+// EdgeHTML has no web message channel, so `window.chrome.webview` is built on
+// top of `window.external.notify`. Messages to the host are sent as JSON.
+const WEB_MESSAGE_SCRIPT: &str = r#"(function () {
+    window.chrome = window.chrome || {};
+    if (window.chrome.webview) return;
+    var listeners = [];
+    window.chrome.webview = {
+        postMessage: function (message) {
+            window.external.notify('__webview_message:' + JSON.stringify(message));
+        },
+        addEventListener: function (type, listener) {
+            if (type === 'message') listeners.push(listener);
+        },
+        removeEventListener: function (type, listener) {
+            var i = listeners.indexOf(listener);
+            if (type === 'message' && i >= 0) listeners.splice(i, 1);
+        },
+        __dispatch: function (data) {
+            listeners.slice().forEach(function (listener) {
+                listener({ data: data });
+            });
+        }
+    };
+})();"#;
+
+const PAGE_STATE_PREFIX: &str = "__webview_state:";
+
+// EdgeHTML has no title-changed event and doesn't report History API
+// navigations, so the page sends its URL and title, as "<url>\n<title>",
+// whenever either changes.
+const PAGE_STATE_SCRIPT: &str = r#"(function () {
+    if (window.__webview_state) return;
+    var last = null;
+    function report() {
+        var state = location.href + '\n' + document.title;
+        if (state === last) return;
+        last = state;
+        window.external.notify('__webview_state:' + state);
+    }
+    window.__webview_state = report;
+    // `<title>` may not exist yet, and may be replaced later.
+    var titleObserver = new MutationObserver(report);
+    var watched = null;
+    function watchTitle() {
+        var title = document.querySelector('head > title');
+        if (title === watched) return;
+        watched = title;
+        titleObserver.disconnect();
+        if (title) {
+            titleObserver.observe(title, { childList: true, characterData: true, subtree: true });
+        }
+        report();
+    }
+    new MutationObserver(watchTitle).observe(document, { childList: true, subtree: true });
+    watchTitle();
+    ['pushState', 'replaceState'].forEach(function (name) {
+        var original = history[name];
+        history[name] = function () {
+            var result = original.apply(this, arguments);
+            report();
+            return result;
+        };
+    });
+    window.addEventListener('popstate', report);
+    window.addEventListener('hashchange', report);
+})();"#;
+
+// Notify strings sent by the crate's own scripts, kept from the handlers
+// registered with `add_script_notify`.
+const RESERVED_PREFIXES: &[&str] = &[WEB_MESSAGE_PREFIX, PAGE_STATE_PREFIX];
+
+fn is_reserved_message(message: &str) -> bool {
+    RESERVED_PREFIXES
+        .iter()
+        .any(|prefix| message.starts_with(prefix))
+}
+
+// Splits a report from `PAGE_STATE_SCRIPT` into its URL and title.
+fn parse_page_state(message: &str) -> Option<(&str, &str)> {
+    if !message.starts_with(PAGE_STATE_PREFIX) {
+        return None;
+    }
+    let mut parts = message[PAGE_STATE_PREFIX.len()..].splitn(2, '\n');
+    match (parts.next(), parts.next()) {
+        (Some(url), Some(title)) => Some((url, title)),
+        _ => None,
+    }
+}
+
+// EdgeHTML has no find API, so matches are wrapped in `<mark>` elements,
+// which are unwrapped again by `stop`. Calls return "<count>,<active>".
+const FIND_SCRIPT: &str = r#"(function () {
+    if (window.__webview_find) return;
+    var marks = [];
+    var active = -1;
+    function result() { return marks.length + ',' + active; }
+    function clear() {
+        marks.forEach(function (mark) {
+            var parent = mark.parentNode;
+            if (!parent) return;
+            parent.replaceChild(document.createTextNode(mark.textContent), mark);
+            parent.normalize();
+        });
+        marks = [];
+        active = -1;
+    }
+    function select(index) {
+        if (active >= 0) marks[active].style.backgroundColor = 'yellow';
+        active = index;
+        marks[active].style.backgroundColor = 'orange';
+        marks[active].scrollIntoView({ block: 'center' });
+    }
+    window.__webview_find = {
+        find: function (query, matchCase, wholeWord) {
+            clear();
+            if (!query || !document.body) return result();
+            var pattern = query.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+            if (wholeWord) pattern = '\\b' + pattern + '\\b';
+            var re = new RegExp(pattern, matchCase ? 'g' : 'gi');
+            var walker = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT, {
+                acceptNode: function (node) {
+                    var name = node.parentNode.nodeName;
+                    return name === 'SCRIPT' || name === 'STYLE' || name === 'NOSCRIPT'
+                        ? NodeFilter.FILTER_REJECT
+                        : NodeFilter.FILTER_ACCEPT;
+                }
+            }, false);
+            var nodes = [];
+            while (walker.nextNode()) nodes.push(walker.currentNode);
+            nodes.forEach(function (node) {
+                var text = node.nodeValue;
+                var last = 0;
+                var fragment = null;
+                var match;
+                re.lastIndex = 0;
+                while ((match = re.exec(text)) !== null) {
+                    fragment = fragment || document.createDocumentFragment();
+                    fragment.appendChild(document.createTextNode(text.slice(last, match.index)));
+                    var mark = document.createElement('mark');
+                    mark.style.backgroundColor = 'yellow';
+                    mark.textContent = match[0];
+                    fragment.appendChild(mark);
+                    marks.push(mark);
+                    last = match.index + match[0].length;
+                }
+                if (fragment) {
+                    fragment.appendChild(document.createTextNode(text.slice(last)));
+                    node.parentNode.replaceChild(fragment, node);
+                }
+            });
+            if (marks.length) select(0);
+            return result();
+        },
+        next: function () {
+            if (marks.length) select((active + 1) % marks.length);
+            return result();
+        },
+        previous: function () {
+            if (marks.length) select((active - 1 + marks.length) % marks.length);
+            return result();
+        },
+        stop: function () {
+            clear();
+            return result();
+        }
+    };
+})();
+"#;
+
+/// What `Control::add_web_resource_requested` does with a request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResourceAction {
+    /// Fetches the resource as usual.
+    Allow,
+    /// Fails the request with `403 Forbidden`.
+    Block,
+    /// Answers the request without fetching anything.
+    Respond {
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    },
+}
+
+fn resource_response(
+    status: u16,
+    headers: &[(String, String)],
+    body: &[u8],
+) -> Result<ComPtr<HttpResponseMessage>, winrt::Error> {
+    let response = HttpResponseMessage::create(HttpStatusCode(status as i32))?;
+    let buffer = CryptographicBuffer::create_from_byte_array(body)?.unwrap();
+    let content = HttpBufferContent::create_from_buffer(&buffer)?;
+    response.set_content(&content.query_interface::<IHttpContent>().unwrap())?;
+
+    // Content headers such as Content-Type are rejected on the response
+    // itself and belong to the body instead.
+    let response_headers = response.get_headers()?.unwrap();
+    let content_headers = content.get_headers()?.unwrap();
+    for (name, value) in headers {
+        let name = FastHString::from(&**name);
+        let value = FastHString::from(&**value);
+        if !response_headers.try_append_without_validation(&name, &value)? {
+            content_headers.try_append_without_validation(&name, &value)?;
+        }
+    }
+    Ok(response)
+}
+
+/// What browser chrome such as an address bar or toolbar shows for the page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageState {
+    pub title: String,
+    pub source: Option<String>,
+    pub can_go_back: bool,
+    pub can_go_forward: bool,
+}
+
+// About one frame, so a burst of navigation events is reported once.
+const STATE_FLUSH_MILLIS: UINT = 16;
+
+thread_local! {
+    static STATE_FLUSHES: RefCell<HashMap<usize, Box<dyn FnOnce()>>> = RefCell::new(HashMap::new());
+}
+
+struct StateBatch<F> {
+    // Weak, since the control's handlers keep the batch alive.
+    inner: Weak<RefCell<ControlInner>>,
+    f: F,
+    scheduled: bool,
+    last: Option<PageState>,
+    // The URL and title the page last reported itself, which the control
+    // doesn't update for History API navigations. Cleared by real ones.
+    reported: Option<(String, String)>,
+}
+
+unsafe extern "system" fn state_flush_timer(_hwnd: HWND, _msg: UINT, id: usize, _time: DWORD) {
+    winuser::KillTimer(ptr::null_mut(), id);
+    let flush = STATE_FLUSHES.with(|flushes| flushes.borrow_mut().remove(&id));
+    if let Some(flush) = flush {
+        flush();
+    }
+}
+
+fn schedule_state_flush<F: FnMut(PageState) + 'static>(batch: &Rc<RefCell<StateBatch<F>>>) {
+    if mem::replace(&mut batch.borrow_mut().scheduled, true) {
+        return;
+    }
+    let id = unsafe {
+        winuser::SetTimer(
+            ptr::null_mut(),
+            0,
+            STATE_FLUSH_MILLIS,
+            Some(state_flush_timer),
+        )
+    };
+    if id == 0 {
+        batch.borrow_mut().scheduled = false;
+        return;
+    }
+    let batch = batch.clone();
+    STATE_FLUSHES.with(|flushes| {
+        flushes
+            .borrow_mut()
+            .insert(id, Box::new(move || flush_state(&batch)))
+    });
+}
+
+fn flush_state<F: FnMut(PageState)>(batch: &RefCell<StateBatch<F>>) {
+    let mut batch = batch.borrow_mut();
+    batch.scheduled = false;
+    let control = match batch.inner.upgrade() {
+        Some(inner) => match inner.borrow().control.clone() {
+            Some(control) => control,
+            None => return,
+        },
+        None => return,
+    };
+    let (title, source) = match batch.reported.clone() {
+        Some((url, title)) => (title, Some(url)),
+        None => (
+            control
+                .get_document_title()
+                .map(|title| title.to_string())
+                .unwrap_or_default(),
+            source(&control),
+        ),
+    };
+    let state = PageState {
+        title,
+        source,
+        can_go_back: control.get_can_go_back().unwrap_or(false),
+        can_go_forward: control.get_can_go_forward().unwrap_or(false),
+    };
+    if batch.last.as_ref() != Some(&state) {
+        batch.last = Some(state.clone());
+        (batch.f)(state);
+    }
+}
+
+type DeferredPermissions = Rc<RefCell<Vec<(u32, PermissionKind, String)>>>;
+
+/// A permission request that was deferred, to be answered later.
+pub struct DeferredPermissionRequest {
+    request: ComPtr<WebViewControlDeferredPermissionRequest>,
+    pending: Option<DeferredPermissions>,
+}
+
+impl DeferredPermissionRequest {
+    pub fn id(&self) -> Result<u32, Error> {
+        Ok(self.request.get_id()?)
+    }
+
+    pub fn kind(&self) -> Result<PermissionKind, Error> {
+        Ok(PermissionKind::from_rt(self.request.get_permission_type()?))
+    }
+
+    pub fn uri(&self) -> Result<String, Error> {
+        Ok(uri_to_string(self.request.get_uri()?)?)
+    }
+
+    pub fn allow(&self) -> Result<(), Error> {
+        self.request.allow()?;
+        self.answered()
+    }
+
+    pub fn deny(&self) -> Result<(), Error> {
+        self.request.deny()?;
+        self.answered()
+    }
+
+    // Drops the request from `Control::deferred_permission_ids` once answered.
+    fn answered(&self) -> Result<(), Error> {
+        if let Some(ref pending) = self.pending {
+            let id = self.request.get_id()?;
+            pending
+                .borrow_mut()
+                .retain(|&(pending, _, _)| pending != id);
+        }
+        Ok(())
+    }
+}
+
+fn deferred_permission_request(
+    control: &WebViewControl,
+    id: u32,
+    pending: Option<DeferredPermissions>,
+) -> Result<Option<DeferredPermissionRequest>, Error> {
+    Ok(control
+        .get_deferred_permission_request_by_id(id)?
+        .map(|request| DeferredPermissionRequest { request, pending }))
+}
+
+pub struct ControlInner {
+    hwnd: HWND,
+    is_window_hwnd: bool,
+
+    control: Option<ComPtr<WebViewControl>>,
+
+    queued_bounds_update: Option<Rect>,
+    zoom_factor: f64,
+    dpi_factor: f64,
+    visible: bool,
+    user_agent: Option<String>,
+    initialization_scripts: Vec<String>,
+    deferred_permissions: DeferredPermissions,
+
+    event_tokens: Vec<(EventRegistrationToken, RemoveEvent)>,
+}
+
+type RemoveEvent = fn(&WebViewControl, EventRegistrationToken) -> Result<(), winrt::Error>;
+
+impl Drop for ControlInner {
+    fn drop(&mut self) {
+        if let Some(ref control) = self.control {
+            for (token, remove) in self.event_tokens.drain(..) {
+                let _ = remove(control, token);
+            }
+        }
+    }
+}
+
+impl ControlInner {
+    fn update_bounds(&mut self) -> Result<(), Error> {
+        let mut rect = RECT {
+            top: 0,
+            left: 0,
+            bottom: 0,
+            right: 0,
+        };
+        // The bounds are relative to the host window's client area, not its
+        // position on screen or the frame around it.
+        if unsafe { winuser::GetClientRect(self.hwnd, &mut rect) } == 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+        self.update_bounds_from_rect(physical_to_dips(rect, self.dpi_factor))
+    }
+
+    // Takes the bounds in DIPs, which is how the control interprets them.
+    fn update_bounds_from_rect(&mut self, rect: Rect) -> Result<(), Error> {
+        if let Some(ref control) = self.control {
+            #[cfg(feature = "log")]
+            log::debug!("Updating bounds to {:?}", rect);
+            let control_site = control.query_interface::<IWebViewControlSite>().unwrap();
+            control_site.set_bounds(rect)?;
+        } else {
+            self.queued_bounds_update = Some(rect);
+        }
+        Ok(())
+    }
+
+    fn apply_user_agent(&self) -> Result<(), Error> {
+        if let (Some(control), Some(user_agent)) = (&self.control, &self.user_agent) {
+            let script = format!(
+                "Object.defineProperty(navigator, 'userAgent', {{ get: function () {{ return {}; }} }});",
+                js_string(user_agent)
+            );
+            control.add_initialize_script(&FastHString::from(&*script))?;
+        }
+        Ok(())
+    }
+
+    fn apply_visible(&self) -> Result<(), Error> {
+        // A control filling a window shares it with the rest of the UI, so
+        // only a host window of its own is hidden.
+        if !self.is_window_hwnd {
+            let show = if self.visible {
+                winuser::SW_SHOW
+            } else {
+                winuser::SW_HIDE
+            };
+            unsafe { winuser::ShowWindow(self.hwnd, show) };
+        }
+        if let Some(ref control) = self.control {
+            control.set_is_visible(self.visible)?;
+        }
+        Ok(())
+    }
+
+    fn apply_zoom_factor(&self) -> Result<(), Error> {
+        if let Some(ref control) = self.control {
+            let control_site = control.query_interface::<IWebViewControlSite>().unwrap();
+            control_site.set_scale(self.zoom_factor)?;
+        }
+        Ok(())
+    }
+}
+
+impl Control {
+    fn control_created(&mut self, web_view_control: Option<ComPtr<WebViewControl>>) {
+        let mut inner = self.inner.borrow_mut();
+        inner.control = web_view_control;
+        // Bounds set while the control was being created win; otherwise go by
+        // the window's current size, which may have changed in the meantime.
+        match inner.queued_bounds_update.take() {
+            Some(rect) => {
+                let _ = inner.update_bounds_from_rect(rect);
+            }
+            None => {
+                let _ = inner.update_bounds();
+            }
+        }
+        if inner.zoom_factor != 1.0 {
+            let _ = inner.apply_zoom_factor();
+        }
+        if !inner.visible {
+            let _ = inner.apply_visible();
+        }
+        if inner.user_agent.is_some() {
+            let _ = inner.apply_user_agent();
+        }
+        if let Some(ref control) = inner.control {
+            for script in &inner.initialization_scripts {
+                let _ = control.add_initialize_script(&FastHString::from(&**script));
+            }
+        }
+    }
+
+    /// Moves or resizes the control, in physical pixels.
+    pub fn resize(
+        &self,
+        position: Option<(i32, i32)>,
+        size: Option<(i32, i32)>,
+    ) -> Result<(), Error> {
+        let mut inner = self.inner.borrow_mut();
         if !inner.is_window_hwnd {
             let (x, y) = position.unwrap_or((0, 0));
             let (width, height) = size.unwrap_or((0, 0));
@@ -274,67 +1565,1202 @@ impl Control {
             if position.is_none() {
                 flags |= winuser::SWP_NOMOVE;
             }
-            if size.is_none() {
-                flags |= winuser::SWP_NOSIZE;
+            if size.is_none() {
+                flags |= winuser::SWP_NOSIZE;
+            }
+            unsafe {
+                winuser::SetWindowPos(inner.hwnd, ptr::null_mut(), x, y, width, height, flags);
+                winuser::UpdateWindow(inner.hwnd);
+            }
+        }
+        if let Some((width, height)) = size {
+            let rect = RECT {
+                left: 0,
+                top: 0,
+                right: width,
+                bottom: height,
+            };
+            let dips = physical_to_dips(rect, inner.dpi_factor);
+            inner.update_bounds_from_rect(dips)?;
+        } else {
+            inner.update_bounds()?;
+        }
+        Ok(())
+    }
+
+    /// Sets the control's bounds within its host window in device-independent
+    /// pixels, the unit the control uses. Overridden by the next `resize` or
+    /// DPI change; bounds set before the control is created are applied once
+    /// it is.
+    pub fn set_bounds_dips(&self, rect: Rect) -> Result<(), Error> {
+        self.inner.borrow_mut().update_bounds_from_rect(rect)
+    }
+
+    /// Sets the control's bounds from physical pixels at `scale` physical
+    /// pixels per DIP, such as the window's DPI factor.
+    pub fn set_bounds_physical(&self, rect: RECT, scale: f64) -> Result<(), Error> {
+        self.set_bounds_dips(physical_to_dips(rect, scale))
+    }
+
+    /// Moves the host window into `new_parent`, keeping its position relative
+    /// to the parent, and recomputes the bounds. A window filled by the
+    /// control becomes a child window with it, and is then sized with
+    /// `resize` like one from `HwndType::NewHwndInWindow`.
+    pub fn reparent(&self, new_parent: HWND) -> Result<(), Error> {
+        let mut inner = self.inner.borrow_mut();
+        unsafe {
+            if inner.is_window_hwnd {
+                let style = winuser::GetWindowLongW(inner.hwnd, winuser::GWL_STYLE) as DWORD;
+                let style = (style | winuser::WS_CHILD)
+                    & !(winuser::WS_POPUP | winuser::WS_OVERLAPPEDWINDOW);
+                winuser::SetWindowLongW(inner.hwnd, winuser::GWL_STYLE, style as i32);
+            }
+            if winuser::SetParent(inner.hwnd, new_parent).is_null() {
+                return Err(Error::Io(io::Error::last_os_error()));
+            }
+        }
+        inner.is_window_hwnd = false;
+        inner.update_bounds()
+    }
+
+    /// Hides or shows the web view without destroying it, so the page keeps
+    /// its state. A hidden control stops painting.
+    pub fn set_visible(&self, visible: bool) -> Result<(), Error> {
+        let mut inner = self.inner.borrow_mut();
+        inner.visible = visible;
+        inner.apply_visible()
+    }
+
+    pub fn visible(&self) -> bool {
+        self.inner.borrow().visible
+    }
+
+    // The scale factor the host window had when the control was created.
+    pub(crate) fn set_dpi_factor(&self, factor: f64) {
+        self.inner.borrow_mut().dpi_factor = factor;
+    }
+
+    /// Re-lays the control out after its window moved to a monitor with a
+    /// different scale factor, such as on winit's `HiDpiFactorChanged`. A
+    /// control in its own child window has that window scaled to match.
+    pub fn handle_dpi_changed(&self, new_factor: f64) -> Result<(), Error> {
+        let mut inner = self.inner.borrow_mut();
+        let scale = new_factor / inner.dpi_factor;
+        inner.dpi_factor = new_factor;
+        if !inner.is_window_hwnd && scale != 1.0 {
+            let mut rect = RECT {
+                top: 0,
+                left: 0,
+                bottom: 0,
+                right: 0,
+            };
+            unsafe {
+                winuser::GetWindowRect(inner.hwnd, &mut rect);
+                winuser::MapWindowPoints(
+                    ptr::null_mut(),
+                    winuser::GetParent(inner.hwnd),
+                    &mut rect as *mut RECT as *mut POINT,
+                    2,
+                );
+                winuser::SetWindowPos(
+                    inner.hwnd,
+                    ptr::null_mut(),
+                    (rect.left as f64 * scale).round() as i32,
+                    (rect.top as f64 * scale).round() as i32,
+                    ((rect.right - rect.left) as f64 * scale).round() as i32,
+                    ((rect.bottom - rect.top) as f64 * scale).round() as i32,
+                    winuser::SWP_NOZORDER | winuser::SWP_NOACTIVATE,
+                );
+            }
+        }
+        inner.update_bounds()
+    }
+
+    /// Sets the page zoom, clamped to `0.25..=5.0`. A factor set before the
+    /// control is created is applied once it is.
+    pub fn set_zoom_factor(&self, factor: f64) -> Result<(), Error> {
+        let mut inner = self.inner.borrow_mut();
+        inner.zoom_factor = factor.max(0.25).min(5.0);
+        inner.apply_zoom_factor()
+    }
+
+    pub fn zoom_factor(&self) -> f64 {
+        self.inner.borrow().zoom_factor
+    }
+
+    /// The URL currently shown, or `None` before the first navigation or
+    /// before the control is created. Same-document `history.pushState`
+    /// changes are only reflected once the control reports a navigation.
+    pub fn source(&self) -> Option<String> {
+        source(&self.get_inner()?)
+    }
+
+    /// Renders the current page to PNG bytes, blocking until it is done.
+    pub fn capture_screenshot(&self) -> Result<Vec<u8>, Error> {
+        let control = self.web_view_control()?;
+        let stream = InMemoryRandomAccessStream::new()
+            .query_interface::<IRandomAccessStream>()
+            .unwrap();
+        wait_for_action(&control.capture_preview_to_stream_async(&stream)?.unwrap())?;
+        read_stream(&stream)
+    }
+
+    /// Like `capture_screenshot`, but returns immediately and hands the PNG
+    /// bytes to `f` once the capture completes.
+    pub fn capture_screenshot_async<F>(&self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Vec<u8>, Error>) + 'static,
+    {
+        let control = self.web_view_control()?;
+        let stream = InMemoryRandomAccessStream::new()
+            .query_interface::<IRandomAccessStream>()
+            .unwrap();
+        let action = control.capture_preview_to_stream_async(&stream)?.unwrap();
+
+        let mut f = FakeSend(Some(f));
+        let stream = FakeSend(stream);
+        action.set_completed(&AsyncActionCompletedHandler::new(move |sender, _status| {
+            if let Some(f) = f.0.take() {
+                let result = unsafe { &*sender }
+                    .get_results()
+                    .map_err(Error::from)
+                    .and_then(|()| read_stream(&stream.0));
+                f(result);
+            }
+            Ok(())
+        }))?;
+        Ok(())
+    }
+
+    /// Like `capture_screenshot_async`, but as a future.
+    #[cfg(feature = "futures")]
+    pub fn capture_screenshot_future(&self) -> EdgeFuture<Vec<u8>> {
+        let (future, complete) = EdgeFuture::new();
+        match self.capture_screenshot_async(complete) {
+            Ok(()) => future,
+            Err(err) => EdgeFuture::ready(Err(err)),
+        }
+    }
+
+    /// Calls the global function `name` in the page with `args` and resolves
+    /// with its result converted to a string.
+    #[cfg(feature = "futures")]
+    pub fn invoke_script_future(&self, name: &str, args: &[&str]) -> EdgeFuture<String> {
+        let operation = self.web_view_control().and_then(|control| {
+            Ok(control
+                .invoke_script_async(&FastHString::from(name), &hstring_iterable(args)?)?
+                .unwrap())
+        });
+        let operation = match operation {
+            Ok(operation) => operation,
+            Err(err) => return EdgeFuture::ready(Err(err)),
+        };
+        operation_future(&operation, |value| value.to_string())
+    }
+
+    /// Clears local storage, session storage and the Cache Storage API for the
+    /// page's origin. The HTTP cache itself belongs to the shared web view
+    /// process and can't be cleared from here.
+    pub fn clear_temporary_data(&self) -> Result<(), Error> {
+        let control = self.web_view_control()?;
+        let script = "localStorage.clear(); sessionStorage.clear(); \
+                      if (window.caches) caches.keys().then(function (keys) { \
+                          keys.forEach(function (key) { caches.delete(key); }); \
+                      }); ''";
+        let operation = control
+            .invoke_script_async(&FastHString::from("eval"), &hstring_iterable(&[script])?)?
+            .unwrap();
+        wait_for(&operation)?;
+        Ok(())
+    }
+
+    /// Navigates to `relative_path` under a `ms-local-stream://` origin whose
+    /// every request, including relative URLs, is answered by `resolver`.
+    pub fn navigate_to_local_stream_uri(
+        &self,
+        relative_path: &str,
+        resolver: StreamResolver,
+    ) -> Result<(), Error> {
+        navigate_to_local_stream_uri(&self.web_view_control()?, relative_path, resolver)
+    }
+
+    /// Navigates to `url` with a referrer or cache mode. These go out as
+    /// request headers, along with any `set_top_level_user_agent` string.
+    pub fn navigate_with_options(&self, url: &str, options: &NavigateOptions) -> Result<(), Error> {
+        let control = self.web_view_control()?;
+        let user_agent = self.inner.borrow().user_agent.clone();
+        navigate_with_options(
+            &control,
+            url,
+            options,
+            user_agent.as_ref().map(|s| s.as_str()),
+        )
+    }
+
+    /// Navigates to a `data:` URI holding `bytes`, for small generated pages
+    /// and images. Payloads over `MAX_DATA_URI_BYTES` are refused; serve those
+    /// with `navigate_to_local_stream_uri` instead.
+    pub fn navigate_to_data_uri(&self, mime: &str, bytes: &[u8]) -> Result<(), Error> {
+        if bytes.len() > MAX_DATA_URI_BYTES {
+            return Err(Error::TooLarge(bytes.len()));
+        }
+        let uri = format!("data:{};base64,{}", mime, base64_encode(bytes));
+        let uri = Uri::create_uri(&FastHString::from(&*uri))?;
+        self.web_view_control()?.navigate(&uri)?;
+        Ok(())
+    }
+
+    /// Registers `js` to run when each document is created, before any of the
+    /// page's own scripts, on this and every later navigation. Scripts run in
+    /// the order they were added, including those added before the control
+    /// was created. A Content-Security-Policy `<meta>` added this way is not
+    /// enforced, and the control has no setting for one; it has to come from
+    /// the server as a response header.
+    pub fn add_initialization_script(&self, js: &str) -> Result<(), Error> {
+        let mut inner = self.inner.borrow_mut();
+        inner.initialization_scripts.push(js.to_owned());
+        if let Some(ref control) = inner.control {
+            control.add_initialize_script(&FastHString::from(js))?;
+        }
+        Ok(())
+    }
+
+    /// The color shown where the page has no background, and before it loads.
+    pub fn set_default_background_color(&self, r: u8, g: u8, b: u8, a: u8) -> Result<(), Error> {
+        self.web_view_control()?
+            .set_default_background_color(UiColor {
+                A: a,
+                R: r,
+                G: g,
+                B: b,
+            })?;
+        Ok(())
+    }
+
+    /// Controls whether pages can send messages with `window.external.notify`,
+    /// the only page-to-host channel EdgeHTML has.
+    pub fn set_is_web_message_enabled(&self, enabled: bool) -> Result<(), Error> {
+        self.settings()?.set_is_script_notify_allowed(enabled)
+    }
+
+    /// Registers a handler for messages the page sends with
+    /// `window.chrome.webview.postMessage`, received as JSON. EdgeHTML has no
+    /// web message event, so this is a script shim over
+    /// `window.external.notify`: the bridge is added to the current page and
+    /// every later one, and script notify is allowed for it. Its messages are
+    /// not passed to `add_script_notify` handlers.
+    pub fn add_web_message_received<F>(&self, mut f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(String) + 'static,
+    {
+        let token = self.add_raw_script_notify(move |message| {
+            if message.starts_with(WEB_MESSAGE_PREFIX) {
+                f(message[WEB_MESSAGE_PREFIX.len()..].to_owned());
+            }
+        })?;
+        self.set_is_web_message_enabled(true)?;
+        self.install_script(WEB_MESSAGE_SCRIPT)?;
+        Ok(token)
+    }
+
+    /// Delivers `message` to the page's `window.chrome.webview` message
+    /// listeners as a string.
+    pub fn post_web_message_as_string(&self, message: &str) -> Result<(), Error> {
+        self.eval_in_page(&format!(
+            "window.chrome && window.chrome.webview && window.chrome.webview.__dispatch({})",
+            js_string(message)
+        ))
+    }
+
+    /// Delivers the parsed `json` to the page's `window.chrome.webview`
+    /// message listeners.
+    pub fn post_web_message_as_json(&self, json: &str) -> Result<(), Error> {
+        self.eval_in_page(&format!(
+            "window.chrome && window.chrome.webview && window.chrome.webview.__dispatch(JSON.parse({}))",
+            js_string(json)
+        ))
+    }
+
+    // Adds `script` to the current page and every later one, once.
+    fn install_script(&self, script: &str) -> Result<(), Error> {
+        let installed = self
+            .inner
+            .borrow()
+            .initialization_scripts
+            .iter()
+            .any(|installed| installed == script);
+        if !installed {
+            self.add_initialization_script(script)?;
+            self.eval_in_page(script)?;
+        }
+        Ok(())
+    }
+
+    // Has the page report title and URL changes that come with no navigation
+    // event, which needs script notify.
+    fn install_page_state_script(&self) -> Result<(), Error> {
+        self.settings()?.set_is_script_notify_allowed(true)?;
+        self.install_script(PAGE_STATE_SCRIPT)
+    }
+
+    // Runs `script` without waiting for it to finish.
+    fn eval_in_page(&self, script: &str) -> Result<(), Error> {
+        let script = format!("{}; ''", script);
+        self.web_view_control()?.invoke_script_async(
+            &FastHString::from("eval"),
+            &hstring_iterable(&[script.as_str()])?,
+        )?;
+        Ok(())
+    }
+
+    pub fn settings(&self) -> Result<Settings, Error> {
+        Ok(Settings {
+            settings: self.web_view_control()?.get_settings()?.unwrap(),
+        })
+    }
+
+    /// Moves keyboard focus into the web content, for example when tabbing
+    /// from native widgets into the control.
+    pub fn move_focus(&self, direction: FocusDirection) -> Result<(), Error> {
+        move_focus(&self.web_view_control()?, direction)
+    }
+
+    /// Gives keyboard focus to the window hosting the control.
+    pub fn focus(&self) {
+        unsafe { winuser::SetFocus(self.inner.borrow().hwnd) };
+    }
+
+    /// Opens the system print dialog for the current page, from which it can
+    /// also be saved with the "Microsoft Print to PDF" printer.
+    ///
+    /// EdgeHTML has no API to print to a PDF file directly, so this is the
+    /// only printing this crate supports.
+    pub fn print(&self) -> Result<(), Error> {
+        self.web_view_control()?.invoke_script_async(
+            &FastHString::from("eval"),
+            &hstring_iterable(&["window.print(); ''"])?,
+        )?;
+        Ok(())
+    }
+
+    /// Highlights every match of `query` in the page and scrolls to the first.
+    /// Matches are only found within a single text node, so text split by
+    /// markup such as `<b>` is not matched across the boundary.
+    pub fn find(&self, query: &str, options: FindOptions) -> Result<FindResult, Error> {
+        self.run_find(&format!(
+            "window.__webview_find.find({}, {}, {})",
+            js_string(query),
+            options.match_case,
+            options.whole_word
+        ))
+    }
+
+    pub fn find_next(&self) -> Result<FindResult, Error> {
+        self.run_find("window.__webview_find.next()")
+    }
+
+    pub fn find_previous(&self) -> Result<FindResult, Error> {
+        self.run_find("window.__webview_find.previous()")
+    }
+
+    /// Removes the highlights left by `find`.
+    pub fn stop_find(&self) -> Result<(), Error> {
+        self.run_find("window.__webview_find.stop()")?;
+        Ok(())
+    }
+
+    fn run_find(&self, call: &str) -> Result<FindResult, Error> {
+        let control = self.web_view_control()?;
+        let script = format!("{}{}", FIND_SCRIPT, call);
+        let operation = control
+            .invoke_script_async(
+                &FastHString::from("eval"),
+                &hstring_iterable(&[script.as_str()])?,
+            )?
+            .unwrap();
+        let result = wait_for(&operation)?.to_string();
+        let mut parts = result
+            .split(',')
+            .map(|part| part.parse::<isize>().unwrap_or(-1));
+        let count = parts.next().unwrap_or(0).max(0) as usize;
+        let active = parts.next().unwrap_or(-1);
+        Ok(FindResult {
+            count,
+            active: if active >= 0 {
+                Some(active as usize)
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Overrides the User-Agent of the top-level navigations started from
+    /// here, `navigate`, `navigate_with_options` and both `refresh` methods,
+    /// and `navigator.userAgent` in pages loaded from then on.
+    ///
+    /// EdgeHTML has no setting for this, neither at creation nor later, so
+    /// subresources, `fetch` and XHR requests, and links the page follows
+    /// itself still carry the default string.
+    pub fn set_top_level_user_agent(&self, user_agent: &str) -> Result<(), Error> {
+        let mut inner = self.inner.borrow_mut();
+        inner.user_agent = Some(user_agent.to_owned());
+        inner.apply_user_agent()
+    }
+
+    pub fn get_cookies(&self, uri: &str) -> Result<Vec<Cookie>, Error> {
+        let uri = Uri::create_uri(&FastHString::from(uri))?;
+        let cookies = cookie_manager()?.get_cookies(&uri)?.unwrap();
+        let mut result = Vec::new();
+        for i in 0..cookies.get_size()? {
+            if let Some(cookie) = cookies.get_at(i)? {
+                result.push(Cookie::from_http_cookie(&cookie)?);
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn set_cookie(&self, cookie: Cookie) -> Result<(), Error> {
+        cookie_manager()?.set_cookie(&cookie.to_http_cookie()?)?;
+        Ok(())
+    }
+
+    /// Deletes every cookie that would be sent to `domain`.
+    pub fn clear_cookies(&self, domain: &str) -> Result<(), Error> {
+        let manager = cookie_manager()?;
+        let uri = Uri::create_uri(&FastHString::from(&*format!("https://{}/", domain)))?;
+        let cookies = manager.get_cookies(&uri)?.unwrap();
+        for i in 0..cookies.get_size()? {
+            if let Some(cookie) = cookies.get_at(i)? {
+                manager.delete_cookie(&cookie)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_hwnd(&self) -> HWND {
+        self.inner.borrow().hwnd
+    }
+
+    pub fn get_inner(&self) -> Option<ComPtr<WebViewControl>> {
+        self.inner.borrow().control.clone()
+    }
+
+    fn web_view_control(&self) -> Result<ComPtr<WebViewControl>, Error> {
+        self.get_inner().ok_or(Error::NotCreated)
+    }
+
+    // Keeps `token` so the handler is removed when the last `Control` is dropped.
+    fn track(&self, token: EventRegistrationToken, remove: RemoveEvent) -> EventRegistrationToken {
+        self.inner.borrow_mut().event_tokens.push((token, remove));
+        token
+    }
+
+    /// Removes a handler registered with one of the `add_*` methods. Unknown or
+    /// already removed tokens are ignored.
+    pub fn remove_event(&self, token: EventRegistrationToken) -> Result<(), Error> {
+        let mut inner = self.inner.borrow_mut();
+        let index = inner
+            .event_tokens
+            .iter()
+            .position(|(tracked, _)| tracked.Value == token.Value);
+        if let Some(index) = index {
+            let (token, remove) = inner.event_tokens.remove(index);
+            if let Some(ref control) = inner.control {
+                remove(control, token)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_navigation_completed<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(bool, String) + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_navigation_completed(&TypedEventHandler::new(
+                move |_sender, args: *mut WebViewControlNavigationCompletedEventArgs| {
+                    let args = unsafe { &mut *args };
+                    f.0(args.get_is_success()?, uri_to_string(args.get_uri()?)?);
+                    Ok(())
+                },
+            ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_navigation_completed(token)
+        }))
+    }
+
+    /// Registers a handler for navigations finishing in an iframe, called with
+    /// the frame's URI and whether the navigation succeeded.
+    pub fn add_frame_navigation_completed<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(&str, bool) + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token =
+            self.web_view_control()?
+                .add_frame_navigation_completed(&TypedEventHandler::new(
+                    move |_sender, args: *mut WebViewControlNavigationCompletedEventArgs| {
+                        let args = unsafe { &mut *args };
+                        f.0(&uri_to_string(args.get_uri()?)?, args.get_is_success()?);
+                        Ok(())
+                    },
+                ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_frame_navigation_completed(token)
+        }))
+    }
+
+    /// Registers a handler for an iframe starting to load new content, called
+    /// with the frame's URI.
+    pub fn add_frame_content_loading<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(&str) + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_frame_content_loading(&TypedEventHandler::new(
+                move |_sender, args: *mut WebViewControlContentLoadingEventArgs| {
+                    let args = unsafe { &mut *args };
+                    f.0(&uri_to_string(args.get_uri()?)?);
+                    Ok(())
+                },
+            ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_frame_content_loading(token)
+        }))
+    }
+
+    /// Registers a handler for the start of a navigation; returning `false`
+    /// from `f` cancels it.
+    pub fn add_navigation_starting<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(String) -> bool + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_navigation_starting(&TypedEventHandler::new(
+                move |_sender, args: *mut WebViewControlNavigationStartingEventArgs| {
+                    let args = unsafe { &mut *args };
+                    if !f.0(uri_to_string(args.get_uri()?)?) {
+                        args.set_cancel(true)?;
+                    }
+                    Ok(())
+                },
+            ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_navigation_starting(token)
+        }))
+    }
+
+    /// Cancels the current navigation or page load.
+    pub fn stop(&self) -> Result<(), Error> {
+        Ok(self.web_view_control()?.stop()?)
+    }
+
+    /// Reloads the page. Does nothing before the first navigation.
+    pub fn refresh(&self) -> Result<(), Error> {
+        if let Some(control) = self.navigated_control()? {
+            if !self.renavigate_with_user_agent(&control, CacheMode::Default)? {
+                control.refresh()?;
             }
-            unsafe {
-                winuser::SetWindowPos(inner.hwnd, ptr::null_mut(), x, y, width, height, flags);
-                winuser::UpdateWindow(inner.hwnd);
+        }
+        Ok(())
+    }
+
+    /// Reloads the page and everything on it from the server. `refresh` has
+    /// no cache option, but EdgeHTML still honors the legacy `forceGet`
+    /// argument to `location.reload`. Does nothing before the first
+    /// navigation.
+    pub fn refresh_ignoring_cache(&self) -> Result<(), Error> {
+        if let Some(control) = self.navigated_control()? {
+            if self.renavigate_with_user_agent(&control, CacheMode::NoCache)? {
+                return Ok(());
             }
+            control.invoke_script_async(
+                &FastHString::from("eval"),
+                &hstring_iterable(&["location.reload(true); ''"])?,
+            )?;
         }
-        if let Some((width, height)) = size {
-            inner.update_bounds_from_rect(Rect {
-                X: 0.0,
-                Y: 0.0,
-                Width: width as f32,
-                Height: height as f32,
-            })?;
-        } else {
-            inner.update_bounds()?;
+        Ok(())
+    }
+
+    // A reload would go out with the default User-Agent, so with one set the
+    // current page is navigated to again instead. Returns whether it was.
+    fn renavigate_with_user_agent(
+        &self,
+        control: &WebViewControl,
+        cache_mode: CacheMode,
+    ) -> Result<bool, Error> {
+        let user_agent = match self.inner.borrow().user_agent {
+            Some(ref user_agent) => user_agent.clone(),
+            None => return Ok(false),
+        };
+        let url = match source(control) {
+            Some(url) => url,
+            None => return Ok(false),
+        };
+        let options = NavigateOptions {
+            cache_mode,
+            ..NavigateOptions::default()
+        };
+        navigate_with_options(control, &url, &options, Some(&user_agent))?;
+        Ok(true)
+    }
+
+    fn navigated_control(&self) -> Result<Option<ComPtr<WebViewControl>>, Error> {
+        match self.get_inner() {
+            Some(control) if control.get_source()?.is_some() => Ok(Some(control)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Registers a handler receiving the loading progress of the top-level
+    /// page from 0.0 to 1.0. EdgeHTML reports no byte-level progress, so the
+    /// value steps through the navigation's stages: starting, content loading,
+    /// DOM ready and completed. Returns the tokens of all four handlers.
+    pub fn add_navigation_progress<F>(&self, f: F) -> Result<Vec<EventRegistrationToken>, Error>
+    where
+        F: FnMut(f64) + 'static,
+    {
+        let control = self.web_view_control()?;
+        let f = Rc::new(RefCell::new(f));
+        let mut tokens = Vec::new();
+
+        let report = FakeSend(f.clone());
+        let token =
+            control.add_navigation_starting(&TypedEventHandler::new(move |_sender, _args| {
+                (&mut *report.0.borrow_mut())(0.0);
+                Ok(())
+            }))?;
+        tokens.push(self.track(token, |control, token| {
+            control.remove_navigation_starting(token)
+        }));
+
+        let report = FakeSend(f.clone());
+        let token =
+            control.add_content_loading(&TypedEventHandler::new(move |_sender, _args| {
+                (&mut *report.0.borrow_mut())(0.3);
+                Ok(())
+            }))?;
+        tokens.push(self.track(token, |control, token| {
+            control.remove_content_loading(token)
+        }));
+
+        let report = FakeSend(f.clone());
+        let token =
+            control.add_dom_content_loaded(&TypedEventHandler::new(move |_sender, _args| {
+                (&mut *report.0.borrow_mut())(0.7);
+                Ok(())
+            }))?;
+        tokens.push(self.track(token, |control, token| {
+            control.remove_dom_content_loaded(token)
+        }));
+
+        let report = FakeSend(f);
+        let token =
+            control.add_navigation_completed(&TypedEventHandler::new(move |_sender, _args| {
+                (&mut *report.0.borrow_mut())(1.0);
+                Ok(())
+            }))?;
+        tokens.push(self.track(token, |control, token| {
+            control.remove_navigation_completed(token)
+        }));
+
+        Ok(tokens)
+    }
+
+    /// Registers a handler receiving the page's title, URL and history state
+    /// together, at most once a frame and only when something changed, so
+    /// browser chrome updates once per navigation. Route changes made with
+    /// `history.pushState` and titles set by script are reported too, for
+    /// which script notify is allowed. Remove the returned tokens with
+    /// `remove_event` to stop it.
+    pub fn on_state_changed<F>(&self, f: F) -> Result<Vec<EventRegistrationToken>, Error>
+    where
+        F: FnMut(PageState) + 'static,
+    {
+        let control = self.web_view_control()?;
+        let batch = Rc::new(RefCell::new(StateBatch {
+            inner: Rc::downgrade(&self.inner),
+            f,
+            scheduled: false,
+            last: None,
+            reported: None,
+        }));
+        let mut tokens = Vec::new();
+
+        let changed = FakeSend(batch.clone());
+        let token =
+            control.add_content_loading(&TypedEventHandler::new(move |_sender, _args| {
+                changed.0.borrow_mut().reported = None;
+                schedule_state_flush(&changed.0);
+                Ok(())
+            }))?;
+        tokens.push(self.track(token, |control, token| {
+            control.remove_content_loading(token)
+        }));
+
+        let changed = FakeSend(batch.clone());
+        let token =
+            control.add_dom_content_loaded(&TypedEventHandler::new(move |_sender, _args| {
+                changed.0.borrow_mut().reported = None;
+                schedule_state_flush(&changed.0);
+                Ok(())
+            }))?;
+        tokens.push(self.track(token, |control, token| {
+            control.remove_dom_content_loaded(token)
+        }));
+
+        let changed = batch.clone();
+        tokens.push(self.add_raw_script_notify(move |message| {
+            if let Some((url, title)) = parse_page_state(&message) {
+                changed.borrow_mut().reported = Some((url.to_owned(), title.to_owned()));
+                schedule_state_flush(&changed);
+            }
+        })?);
+
+        let changed = FakeSend(batch);
+        let token =
+            control.add_navigation_completed(&TypedEventHandler::new(move |_sender, _args| {
+                changed.0.borrow_mut().reported = None;
+                schedule_state_flush(&changed.0);
+                Ok(())
+            }))?;
+        tokens.push(self.track(token, |control, token| {
+            control.remove_navigation_completed(token)
+        }));
+
+        self.install_page_state_script()?;
+        Ok(tokens)
+    }
+
+    /// Registers a handler called with the URI of every resource the page
+    /// requests over HTTP(S), before it is fetched, to let it through, block
+    /// it or answer it directly. Requests answered by a `StreamResolver` are
+    /// not reported; filter those in the resolver instead.
+    pub fn add_web_resource_requested<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(&str) -> ResourceAction + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token =
+            self.web_view_control()?
+                .add_web_resource_requested(&TypedEventHandler::new(
+                    move |_sender, args: *mut WebViewControlWebResourceRequestedEventArgs| {
+                        let args = unsafe { &mut *args };
+                        let request = args.get_request()?.unwrap();
+                        let uri = uri_to_string(request.get_request_uri()?)?;
+                        let response = match f.0(&uri) {
+                            ResourceAction::Allow => return Ok(()),
+                            ResourceAction::Block => resource_response(403, &[], &[])?,
+                            ResourceAction::Respond {
+                                status,
+                                headers,
+                                body,
+                            } => resource_response(status, &headers, &body)?,
+                        };
+                        args.set_response(&response)?;
+                        Ok(())
+                    },
+                ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_web_resource_requested(token)
+        }))
+    }
+
+    /// Registers a handler called periodically while a page script keeps the
+    /// page busy, with how long it has been running. Returning `true` stops
+    /// the script.
+    pub fn add_long_running_script_detected<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(Duration) -> bool + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token =
+            self.web_view_control()?
+                .add_long_running_script_detected(&TypedEventHandler::new(
+                    move |_sender, args: *mut WebViewControlLongRunningScriptDetectedEventArgs| {
+                        let args = unsafe { &mut *args };
+                        // `TimeSpan` counts 100ns ticks.
+                        let ticks = args.get_execution_time()?.Duration.max(0) as u64;
+                        let elapsed = Duration::from_nanos(ticks * 100);
+                        if f.0(elapsed) {
+                            args.set_stop_page_script_execution(true)?;
+                        }
+                        Ok(())
+                    },
+                ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_long_running_script_detected(token)
+        }))
+    }
+
+    /// Registers a handler for `window.open` and `target="_blank"` links,
+    /// called with the requested URI and the referrer. Returning `true` marks
+    /// the request as handled.
+    pub fn add_new_window_requested<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(&str, &str) -> bool + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_new_window_requested(&TypedEventHandler::new(
+                move |_sender, args: *mut WebViewControlNewWindowRequestedEventArgs| {
+                    let args = unsafe { &mut *args };
+                    let uri = uri_to_string(args.get_uri()?)?;
+                    let referrer = uri_to_string(args.get_referrer()?)?;
+                    if f.0(&uri, &referrer) {
+                        args.set_handled(true)?;
+                    }
+                    Ok(())
+                },
+            ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_new_window_requested(token)
+        }))
+    }
+
+    /// Registers a handler for links to schemes the web view can't open, such
+    /// as `mailto:`. Returning `true` marks the URI as handled; `false` leaves
+    /// the default behavior in place.
+    pub fn add_unsupported_uri_scheme_identified<F>(
+        &self,
+        f: F,
+    ) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(&str) -> bool + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_unsupported_uri_scheme_identified(&TypedEventHandler::new(
+                move |_sender, args: *mut WebViewControlUnsupportedUriSchemeIdentifiedEventArgs| {
+                    let args = unsafe { &mut *args };
+                    if f.0(&uri_to_string(args.get_uri()?)?) {
+                        args.set_handled(true)?;
+                    }
+                    Ok(())
+                },
+            ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_unsupported_uri_scheme_identified(token)
+        }))
+    }
+
+    /// Registers a handler deciding permission requests, such as for the
+    /// camera or location, from the page at the given URI.
+    pub fn add_permission_requested<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(PermissionKind, &str) -> PermissionDecision + 'static,
+    {
+        let mut f = FakeSend(f);
+        let deferred = FakeSend(self.inner.borrow().deferred_permissions.clone());
+        let token = self
+            .web_view_control()?
+            .add_permission_requested(&TypedEventHandler::new(
+                move |_sender, args: *mut WebViewControlPermissionRequestedEventArgs| {
+                    let request = unsafe { &mut *args }.get_permission_request()?.unwrap();
+                    let kind = PermissionKind::from_rt(request.get_permission_type()?);
+                    let uri = uri_to_string(request.get_uri()?)?;
+                    match f.0(kind, &uri) {
+                        PermissionDecision::Allow => request.allow()?,
+                        PermissionDecision::Deny => request.deny()?,
+                        PermissionDecision::Defer => {
+                            request.defer()?;
+                            deferred.0.borrow_mut().push((request.get_id()?, kind, uri));
+                        }
+                    }
+                    Ok(())
+                },
+            ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_permission_requested(token)
+        }))
+    }
+
+    /// Allows or denies every deferred request of `kind` from `uri`.
+    pub fn resolve_deferred_permission(
+        &self,
+        kind: PermissionKind,
+        uri: &str,
+        allow: bool,
+    ) -> Result<(), Error> {
+        let control = self.web_view_control()?;
+        let deferred = self.inner.borrow().deferred_permissions.clone();
+        let mut pending = deferred.borrow_mut();
+        let mut i = 0;
+        while i < pending.len() {
+            if pending[i].1 != kind || pending[i].2 != uri {
+                i += 1;
+                continue;
+            }
+            let (id, _, _) = pending.remove(i);
+            if let Some(request) = deferred_permission_request(&control, id, None)? {
+                if allow {
+                    request.allow()?;
+                } else {
+                    request.deny()?;
+                }
+            }
         }
         Ok(())
     }
 
-    pub fn get_hwnd(&self) -> HWND {
-        self.inner.borrow().hwnd
+    /// The ids of deferred permission requests that are still unanswered, oldest
+    /// first.
+    pub fn deferred_permission_ids(&self) -> Vec<u32> {
+        self.inner
+            .borrow()
+            .deferred_permissions
+            .borrow()
+            .iter()
+            .map(|&(id, _, _)| id)
+            .collect()
     }
 
-    pub fn get_inner(&self) -> Option<ComPtr<WebViewControl>> {
-        self.inner.borrow().control.clone()
+    /// Looks up a request deferred by a permission handler, or `None` if `id`
+    /// is unknown or the request has already been answered.
+    pub fn get_deferred_permission_request_by_id(
+        &self,
+        id: u32,
+    ) -> Result<Option<DeferredPermissionRequest>, Error> {
+        let pending = self.inner.borrow().deferred_permissions.clone();
+        deferred_permission_request(&self.web_view_control()?, id, Some(pending))
+    }
+
+    /// Registers a handler called with `true` when an element such as a
+    /// `<video>` enters HTML5 fullscreen and `false` when it leaves. The control
+    /// only reports the change; resizing the host window is up to the handler.
+    pub fn add_contains_full_screen_element_changed<F>(
+        &self,
+        f: F,
+    ) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(bool) + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_contains_full_screen_element_changed(&TypedEventHandler::new(
+                move |sender: *mut IWebViewControl, _args| {
+                    let sender = unsafe { &mut *sender };
+                    f.0(sender.get_contains_full_screen_element()?);
+                    Ok(())
+                },
+            ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_contains_full_screen_element_changed(token)
+        }))
+    }
+
+    /// Registers a handler called when keyboard focus moves into the web
+    /// content, for example to update toolbar state.
+    pub fn add_got_focus<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut() + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_got_focus(&TypedEventHandler::new(move |_sender, _args| {
+                f.0();
+                Ok(())
+            }))?;
+        Ok(self.track(token, |control, token| control.remove_got_focus(token)))
+    }
+
+    /// Registers a handler called when keyboard focus leaves the web content.
+    pub fn add_lost_focus<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut() + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_lost_focus(&TypedEventHandler::new(move |_sender, _args| {
+                f.0();
+                Ok(())
+            }))?;
+        Ok(self.track(token, |control, token| control.remove_lost_focus(token)))
+    }
+
+    /// Registers a handler for strings the page passes to
+    /// `window.external.notify`, which needs script notify to be allowed in
+    /// `settings`. Strings sent by this crate's own page scripts are left out.
+    pub fn add_script_notify<F>(&self, mut f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(String) + 'static,
+    {
+        self.add_raw_script_notify(move |value| {
+            if !is_reserved_message(&value) {
+                f(value);
+            }
+        })
+    }
+
+    // Like `add_script_notify`, but also gets the reserved strings.
+    fn add_raw_script_notify<F>(&self, f: F) -> Result<EventRegistrationToken, Error>
+    where
+        F: FnMut(String) + 'static,
+    {
+        let mut f = FakeSend(f);
+        let token = self
+            .web_view_control()?
+            .add_script_notify(&TypedEventHandler::new(
+                move |_sender, args: *mut WebViewControlScriptNotifyEventArgs| {
+                    let args = unsafe { &mut *args };
+                    let value = args.get_value().map(|s| s.to_string())?;
+                    f.0(value);
+                    Ok(())
+                },
+            ))?;
+        Ok(self.track(token, |control, token| control.remove_script_notify(token)))
+    }
+
+    /// Like `add_script_notify`, but the handler is removed when the returned
+    /// subscription is dropped.
+    pub fn on_script_notify<F>(&self, f: F) -> Result<ScriptNotifySubscription, Error>
+    where
+        F: FnMut(String) + 'static,
+    {
+        let token = self.add_script_notify(f)?;
+        Ok(ScriptNotifySubscription {
+            inner: Rc::downgrade(&self.inner),
+            token,
+        })
+    }
+
+    /// Registers a handler that receives the document title whenever it
+    /// changes, whether by navigating or by script setting `document.title`.
+    /// Script notify is allowed so the page can report the latter. Returns the
+    /// tokens of both underlying handlers.
+    pub fn add_document_title_changed<F>(
+        &self,
+        mut f: F,
+    ) -> Result<Vec<EventRegistrationToken>, Error>
+    where
+        F: FnMut(String) + 'static,
+    {
+        let control = self.web_view_control()?;
+        let mut last_title = None;
+        let changed = Rc::new(RefCell::new(move |title: String| {
+            if last_title.as_ref() != Some(&title) {
+                last_title = Some(title.clone());
+                f(title);
+            }
+        }));
+        let mut tokens = Vec::new();
+
+        let report = changed.clone();
+        tokens.push(self.add_raw_script_notify(move |message| {
+            if let Some((_, title)) = parse_page_state(&message) {
+                (&mut *report.borrow_mut())(title.to_owned());
+            }
+        })?);
+
+        // Covers pages loaded before the script could observe them.
+        let report = FakeSend(changed);
+        let token = control.add_navigation_completed(&TypedEventHandler::new(
+            move |sender: *mut IWebViewControl, _args| {
+                let sender = unsafe { &mut *sender };
+                let title = sender.get_document_title()?.to_string();
+                (&mut *report.0.borrow_mut())(title);
+                Ok(())
+            },
+        ))?;
+        tokens.push(self.track(token, |control, token| {
+            control.remove_navigation_completed(token)
+        }));
+
+        self.install_page_state_script()?;
+        Ok(tokens)
     }
 }
 
-pub trait WebView {
-    type Error;
-    fn navigate(&self, url: &str) -> Result<(), Self::Error>;
-    fn navigate_to_string(&self, url: &str) -> Result<(), Self::Error>;
+#[cfg(feature = "raw-window-handle")]
+unsafe impl raw_window_handle::HasRawWindowHandle for Control {
+    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
+        raw_window_handle::RawWindowHandle::Windows(raw_window_handle::windows::WindowsHandle {
+            hwnd: self.get_hwnd() as *mut c_void,
+            ..raw_window_handle::windows::WindowsHandle::empty()
+        })
+    }
 }
 
 impl WebView for Control {
-    type Error = winrt::Error;
-    fn navigate(&self, url: &str) -> Result<(), winrt::Error> {
-        if let Some(ref control) = self.inner.borrow().control {
-            control.navigate(&*Uri::create_uri(&FastHString::from(&*url))?)?;
-        }
-        Ok(())
+    type Error = Error;
+    fn navigate(&self, url: &str) -> Result<(), Error> {
+        self.navigate_with_options(url, &NavigateOptions::default())
     }
 
-    fn navigate_to_string(&self, url: &str) -> Result<(), winrt::Error> {
-        let mut file = File::open(url).unwrap();
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-        if let Some(ref control) = self.inner.borrow().control {
-            control.navigate_to_string(&FastHString::from(contents.as_str()))?;
-        }
+    fn navigate_to_string(&self, html: &str) -> Result<(), Error> {
+        self.web_view_control()?
+            .navigate_to_string(&FastHString::from(html))?;
         Ok(())
     }
+
+    fn navigate_to_file(&self, path: &Path) -> Result<(), Error> {
+        let contents = fs::read_to_string(path)?;
+        self.navigate_to_string(&contents)
+    }
 }
 
 pub struct EdgeWebViewControl {
     control: ComPtr<WebViewControl>,
+    closed: Cell<bool>,
+    event_tokens: RefCell<Vec<(EventRegistrationToken, RemoveEvent)>>,
+}
+
+impl Drop for EdgeWebViewControl {
+    fn drop(&mut self) {
+        for (token, remove) in self.event_tokens.get_mut().drain(..) {
+            let _ = remove(&self.control, token);
+        }
+    }
 }
 
 impl EdgeWebViewControl {
+    pub fn new(control: ComPtr<WebViewControl>) -> EdgeWebViewControl {
+        EdgeWebViewControl {
+            control,
+            closed: Cell::new(false),
+            event_tokens: RefCell::new(Vec::new()),
+        }
+    }
+
+    // Keeps `token` so the handler is removed when this is dropped.
+    fn track(&self, token: EventRegistrationToken, remove: RemoveEvent) -> EventRegistrationToken {
+        self.event_tokens.borrow_mut().push((token, remove));
+        token
+    }
+
+    /// Removes a handler registered with one of the `add_*` methods. Unknown or
+    /// already removed tokens are ignored.
+    pub fn remove_event(&self, token: EventRegistrationToken) -> Result<(), winrt::Error> {
+        let mut event_tokens = self.event_tokens.borrow_mut();
+        let index = event_tokens
+            .iter()
+            .position(|(tracked, _)| tracked.Value == token.Value);
+        if let Some(index) = index {
+            let (token, remove) = event_tokens.remove(index);
+            remove(&self.control, token)?;
+        }
+        Ok(())
+    }
+
+    fn live_control(&self) -> Result<&ComPtr<WebViewControl>, Error> {
+        if self.closed.get() {
+            Err(Error::Closed)
+        } else {
+            Ok(&self.control)
+        }
+    }
+
     pub fn can_go_back(&self) -> bool {
         self.control.get_can_go_back().unwrap_or(false)
     }
@@ -356,19 +2782,82 @@ impl EdgeWebViewControl {
             .unwrap_or(String::new())
     }
 
-    pub fn capture_selected_content_to_data_package_async(&self) {}
-    pub fn close(&self) {}
-    pub fn get_deferred_permission_request_by_id(&self) {}
-    pub fn go_back(&self) {}
-    pub fn go_forward(&self) {}
-    pub fn invoke_script_async(&self) {}
-    pub fn move_focus(&self) {}
-    pub fn navigate(&self) {}
-    pub fn navigate_to_local_stream_uri(&self) {}
-    pub fn navigate_to_string(&self) {}
-    pub fn navigate_with_http_request_message(&self) {}
-    pub fn refresh(&self) {}
-    pub fn stop(&self) {}
+    pub fn source(&self) -> Option<String> {
+        source(&self.control)
+    }
+
+    pub fn get_deferred_permission_request_by_id(
+        &self,
+        id: u32,
+    ) -> Result<Option<DeferredPermissionRequest>, Error> {
+        deferred_permission_request(&self.live_control()?, id, None)
+    }
+
+    /// Navigates to `url` with a referrer or cache mode, sent as request
+    /// headers.
+    pub fn navigate_with_options(&self, url: &str, options: &NavigateOptions) -> Result<(), Error> {
+        navigate_with_options(self.live_control()?, url, options, None)
+    }
+
+    pub fn move_focus(&self, direction: FocusDirection) -> Result<(), Error> {
+        move_focus(&self.live_control()?, direction)
+    }
+
+    /// Disposes the underlying control. Later navigation and script calls fail
+    /// with `Error::Closed`; closing again does nothing.
+    pub fn close(&self) -> Result<(), Error> {
+        if !self.closed.replace(true) {
+            let control_site = self
+                .control
+                .query_interface::<IWebViewControlSite>()
+                .unwrap();
+            control_site.close()?;
+        }
+        Ok(())
+    }
+
+    pub fn invoke_script_async(&self, name: &str, args: &[&str]) -> Result<String, Error> {
+        let operation = self
+            .live_control()?
+            .invoke_script_async(&FastHString::from(name), &hstring_iterable(args)?)?
+            .unwrap();
+        Ok(wait_for(&operation)?.to_string())
+    }
+
+    pub fn go_back(&self) -> Result<(), Error> {
+        Ok(self.live_control()?.go_back()?)
+    }
+
+    pub fn go_forward(&self) -> Result<(), Error> {
+        Ok(self.live_control()?.go_forward()?)
+    }
+
+    pub fn navigate(&self, url: &str) -> Result<(), Error> {
+        let uri = Uri::create_uri(&FastHString::from(url))?;
+        Ok(self.live_control()?.navigate(&*uri)?)
+    }
+
+    pub fn navigate_to_string(&self, html: &str) -> Result<(), Error> {
+        Ok(self
+            .live_control()?
+            .navigate_to_string(&FastHString::from(html))?)
+    }
+
+    pub fn navigate_to_local_stream_uri(
+        &self,
+        relative_path: &str,
+        resolver: StreamResolver,
+    ) -> Result<(), Error> {
+        navigate_to_local_stream_uri(&self.live_control()?, relative_path, resolver)
+    }
+
+    pub fn refresh(&self) -> Result<(), Error> {
+        Ok(self.live_control()?.refresh()?)
+    }
+
+    pub fn stop(&self) -> Result<(), Error> {
+        Ok(self.live_control()?.stop()?)
+    }
 
     pub fn add_contains_full_screen_element_changed<F>(
         &self,
@@ -378,14 +2867,18 @@ impl EdgeWebViewControl {
         F: FnMut(bool) + 'static,
     {
         let mut f = FakeSend(f);
-        self.control
-            .add_contains_full_screen_element_changed(&TypedEventHandler::new(
-                move |sender: *mut IWebViewControl, _args| {
-                    let sender = unsafe { &mut *sender };
-                    f.0(sender.get_contains_full_screen_element()?);
-                    Ok(())
-                },
-            ))
+        let token =
+            self.control
+                .add_contains_full_screen_element_changed(&TypedEventHandler::new(
+                    move |sender: *mut IWebViewControl, _args| {
+                        let sender = unsafe { &mut *sender };
+                        f.0(sender.get_contains_full_screen_element()?);
+                        Ok(())
+                    },
+                ))?;
+        Ok(self.track(token, |control, token| {
+            control.remove_contains_full_screen_element_changed(token)
+        }))
     }
 
     pub fn add_script_notify<F>(&self, f: F) -> Result<EventRegistrationToken, winrt::Error>
@@ -393,13 +2886,14 @@ impl EdgeWebViewControl {
         F: FnMut(String) + 'static,
     {
         let mut f = FakeSend(f);
-        self.control.add_script_notify(&TypedEventHandler::new(
+        let token = self.control.add_script_notify(&TypedEventHandler::new(
             move |_sender, args: *mut WebViewControlScriptNotifyEventArgs| {
                 let args = unsafe { &mut *args };
                 let value = args.get_value().map(|s| s.to_string())?;
                 f.0(value);
                 Ok(())
             },
-        ))
+        ))?;
+        Ok(self.track(token, |control, token| control.remove_script_notify(token)))
     }
 }
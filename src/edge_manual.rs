@@ -2,66 +2,185 @@ use include_dir::Dir;
 use webview_sys as ffi;
 
 use ffi::*;
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
-use std::fmt;
+use std::fs;
 use std::marker::PhantomData;
 use std::os::raw::*;
-use std::path::Path;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Component, Path, PathBuf};
 use std::ptr;
+use std::time::{Duration, UNIX_EPOCH};
+pub(crate) use webview_api::js_string;
 
 pub enum Content<'a, S: Into<String>> {
     Html(S),
     Url(S),
     Dir(Dir<'a>, S),
+    /// Like `Dir`, but reads each file from this directory when it is
+    /// requested, so a rebuilt frontend shows up on reload. Meant for
+    /// development; release builds should embed their files with `Dir`.
+    DirPath(PathBuf, S),
+    Bytes {
+        data: Vec<u8>,
+        mime: S,
+    },
+}
+
+// `webview_loop` waits without a timeout when given this.
+const INFINITE: u32 = 0xFFFF_FFFF;
+
+const BYTES_SOURCE: &str = "/__webview_content";
+const SPA_INDEX: &str = "index.html";
+const VERSION_SOURCE: &str = "/__webview_version";
+
+// Appended to HTML pages from a `Content::DirPath` with reloading on change.
+const RELOAD_SCRIPT: &str = "<script>(function () {
+    var version = null;
+    setInterval(function () {
+        fetch('/__webview_version').then(function (response) {
+            return response.text();
+        }).then(function (text) {
+            if (version !== null && text !== version) location.reload();
+            version = text;
+        });
+    }, 1000);
+})();</script>";
+
+static MIME_TYPES: &[(&str, &[u8])] = &[
+    ("css", b"text/css\0"),
+    ("gif", b"image/gif\0"),
+    ("htm", b"text/html\0"),
+    ("html", b"text/html\0"),
+    ("ico", b"image/x-icon\0"),
+    ("jpeg", b"image/jpeg\0"),
+    ("jpg", b"image/jpeg\0"),
+    ("js", b"application/javascript\0"),
+    ("json", b"application/json\0"),
+    ("mjs", b"application/javascript\0"),
+    ("mp3", b"audio/mpeg\0"),
+    ("mp4", b"video/mp4\0"),
+    ("otf", b"font/otf\0"),
+    ("png", b"image/png\0"),
+    ("svg", b"image/svg+xml\0"),
+    ("ttf", b"font/ttf\0"),
+    ("txt", b"text/plain\0"),
+    ("wasm", b"application/wasm\0"),
+    ("webm", b"video/webm\0"),
+    ("webp", b"image/webp\0"),
+    ("woff", b"font/woff\0"),
+    ("woff2", b"font/woff2\0"),
+    ("xml", b"application/xml\0"),
+];
+
+fn mime_type(path: &Path) -> &'static [u8] {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| {
+            MIME_TYPES
+                .iter()
+                .find(|(known, _)| known.eq_ignore_ascii_case(ext))
+        })
+        .map(|(_, mime)| *mime)
+        .unwrap_or(b"application/octet-stream\0")
+}
+
+pub struct Resource {
+    pub data: Vec<u8>,
+    pub mime: String,
 }
 
 pub enum Event {
     Quit,
     DOMContentLoaded,
     ScriptNotify(String),
+    /// The new client size of the window. Interactive resizing is reported
+    /// once, when the user lets go.
+    Resized(i32, i32),
+    /// Native paths of files dropped onto the window.
+    FileDrop(Vec<PathBuf>),
 }
 
-#[derive(Debug)]
-pub enum Error {
-    Null(std::ffi::NulError),
-    Runtime(i32, String),
+/// A request sent from the page with `webviewRequest(body)`, which returns a
+/// promise for the string passed to `WebView::reply` with the same `id`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptNotifyMessage {
+    pub id: u64,
+    pub body: String,
 }
 
-impl From<std::ffi::NulError> for Error {
-    fn from(err: std::ffi::NulError) -> Error {
-        Error::Null(err)
-    }
-}
+const RPC_PREFIX: &str = "__webview_rpc:";
 
-impl std::error::Error for Error {
-    fn description(&self) -> &str {
-        match *self {
-            Error::Null(ref err) => err.description(),
-            Error::Runtime(_, ref message) => message.as_str(),
+impl ScriptNotifyMessage {
+    /// Returns `None` for notifications that aren't requests, such as those
+    /// sent with `window.external.notify` directly.
+    pub fn parse(message: &str) -> Option<ScriptNotifyMessage> {
+        if !message.starts_with(RPC_PREFIX) {
+            return None;
         }
+        let rest = &message[RPC_PREFIX.len()..];
+        let separator = rest.find(':')?;
+        Some(ScriptNotifyMessage {
+            id: rest[..separator].parse().ok()?,
+            body: rest[separator + 1..].to_owned(),
+        })
     }
 }
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            Error::Null(ref err) => write!(f, "Null error: {}", err),
-            Error::Runtime(code, ref message) => {
-                write!(f, "Windows Runtime error 0x{:08x}: \"{}\"", code, message)
-            }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Options,
+}
+
+impl HttpMethod {
+    fn as_str(self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Patch => "PATCH",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Options => "OPTIONS",
         }
     }
 }
 
+/// Returned from a `WebView::run` handler to keep running or stop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlFlow {
+    Continue,
+    Exit,
+}
+
+pub use crate::error::Error;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub struct WebView<'a> {
     window: *mut c_void,
     internal: Box<InternalData<'a>>,
+    next_style_id: u32,
 }
 
+/// Identifies a style sheet added with `WebView::inject_css`.
+#[derive(Debug)]
+pub struct StyleHandle(u32);
+
 struct InternalData<'a> {
     dir: Option<include_dir::Dir<'a>>,
+    bytes: Option<(Vec<u8>, CString)>,
+    protocols: HashMap<String, Box<dyn FnMut(&str) -> Option<Resource> + 'a>>,
+    dir_path: Option<PathBuf>,
+    response: Option<(String, Vec<u8>, CString)>,
+    spa_fallback: bool,
+    reload_on_change: bool,
 }
 
 pub struct Dispatcher<'a> {
@@ -70,6 +189,16 @@ pub struct Dispatcher<'a> {
     webview: *mut c_void,
 }
 
+struct Completion<F: FnOnce(Result<String>)>(Option<F>);
+
+impl<F: FnOnce(Result<String>)> Drop for Completion<F> {
+    fn drop(&mut self) {
+        if let Some(callback) = self.0.take() {
+            callback(Err(Error::Closed));
+        }
+    }
+}
+
 struct CallbackInfo<'a> {
     callback: Box<FnMut(&'a mut WebView<'a>) + 'a>,
 }
@@ -77,28 +206,197 @@ struct CallbackInfo<'a> {
 pub struct EventIterator<'a> {
     phantom: PhantomData<&'a WebView<'a>>,
     window: *mut c_void,
-    blocking: bool,
+    timeout: u32,
 }
 
-impl<'a> WebView<'a> {
-    pub fn new<S: Into<String>>(
-        title: &str,
-        content: Content<'a, S>,
-        size: (i32, i32),
-        resizable: bool,
-    ) -> Result<WebView<'a>> {
-        let title = CString::new(title)?;
+/// Configures and creates a `WebView`. Unset options default to an empty,
+/// resizable 800×600 window with no minimum size.
+pub struct WebViewBuilder<'a> {
+    title: String,
+    content: Content<'a, String>,
+    size: (i32, i32),
+    resizable: bool,
+    min_size: Option<(i32, i32)>,
+    debug: bool,
+    spa_fallback: bool,
+    reload_on_change: bool,
+}
+
+impl<'a> WebViewBuilder<'a> {
+    pub fn new() -> WebViewBuilder<'a> {
+        WebViewBuilder {
+            title: String::new(),
+            content: Content::Html(String::new()),
+            size: (800, 600),
+            resizable: true,
+            min_size: None,
+            debug: false,
+            spa_fallback: false,
+            reload_on_change: false,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_owned();
+        self
+    }
+
+    pub fn content<S: Into<String>>(mut self, content: Content<'a, S>) -> Self {
+        self.content = match content {
+            Content::Html(html) => Content::Html(html.into()),
+            Content::Url(url) => Content::Url(url.into()),
+            Content::Dir(dir, source) => Content::Dir(dir, source.into()),
+            Content::DirPath(path, source) => Content::DirPath(path, source.into()),
+            Content::Bytes { data, mime } => Content::Bytes {
+                data,
+                mime: mime.into(),
+            },
+        };
+        self
+    }
+
+    pub fn size(mut self, width: i32, height: i32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// The smallest outer size the user can resize the window to.
+    pub fn min_size(mut self, width: i32, height: i32) -> Self {
+        self.min_size = Some((width, height));
+        self
+    }
+
+    /// Launches the Edge DevTools Preview app once the window is created.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Serves `index.html` from a `Content::Dir` or `Content::DirPath` for
+    /// unknown paths without a file extension, so history-API routes survive
+    /// reloads and deep links. Missing assets such as `/app.js` still fail to
+    /// load.
+    pub fn spa_fallback(mut self, spa_fallback: bool) -> Self {
+        self.spa_fallback = spa_fallback;
+        self
+    }
+
+    /// Reloads pages from a `Content::DirPath` when a file under the directory
+    /// is added, removed or modified. The pages poll for changes once a
+    /// second through a script appended to their HTML.
+    pub fn reload_on_change(mut self, reload_on_change: bool) -> Self {
+        self.reload_on_change = reload_on_change;
+        self
+    }
+
+    pub fn build(self) -> Result<WebView<'a>> {
+        let title = CString::new(self.title)?;
         let window = ffi_result(unsafe {
             let mut window: *mut c_void = ptr::null_mut();
-            let result = webview_new(title.as_ptr(), size.0, size.1, resizable, &mut window);
+            let result = webview_new(
+                title.as_ptr(),
+                self.size.0,
+                self.size.1,
+                self.resizable,
+                &mut window,
+            );
             (window, result)
         })?;
 
         let mut webview = WebView {
             window,
-            internal: Box::new(InternalData { dir: None }),
+            internal: Box::new(InternalData {
+                dir: None,
+                bytes: None,
+                protocols: HashMap::new(),
+                dir_path: None,
+                response: None,
+                spa_fallback: self.spa_fallback,
+                reload_on_change: self.reload_on_change,
+            }),
+            next_style_id: 0,
         };
-        let internal = webview.internal.as_mut() as *mut InternalData as *mut c_void;
+        webview.navigate(self.content)?;
+
+        if let Some((width, height)) = self.min_size {
+            ffi_result(unsafe { ((), webview_set_min_size(window, width, height)) })?;
+        }
+        if self.debug {
+            ffi_result(unsafe { ((), webview_open_dev_tools(window)) })?;
+        }
+
+        Ok(webview)
+    }
+}
+
+impl<'a> Default for WebViewBuilder<'a> {
+    fn default() -> Self {
+        WebViewBuilder::new()
+    }
+}
+
+impl<'a> WebView<'a> {
+    pub fn new<S: Into<String>>(
+        title: &str,
+        content: Content<'a, S>,
+        size: (i32, i32),
+        resizable: bool,
+    ) -> Result<WebView<'a>> {
+        WebViewBuilder::new()
+            .title(title)
+            .content(content)
+            .size(size.0, size.1)
+            .resizable(resizable)
+            .build()
+    }
+
+    pub fn dispatcher(&mut self) -> Dispatcher<'a> {
+        Dispatcher {
+            phantom: PhantomData,
+            window: self.window,
+            webview: self as *mut WebView as *mut c_void,
+        }
+    }
+
+    pub fn poll_iter(&self) -> EventIterator<'a> {
+        EventIterator {
+            phantom: PhantomData,
+            window: self.window,
+            timeout: 0,
+        }
+    }
+
+    pub fn wait_iter(&self) -> EventIterator<'a> {
+        EventIterator {
+            phantom: PhantomData,
+            window: self.window,
+            timeout: INFINITE,
+        }
+    }
+
+    /// Blocks up to `timeout` for each event, yielding `None` when none arrived
+    /// in time so periodic work can run without spinning.
+    pub fn wait_timeout_iter(&self, timeout: Duration) -> EventIterator<'a> {
+        let millis = timeout.as_millis().min(INFINITE as u128 - 1) as u32;
+        EventIterator {
+            phantom: PhantomData,
+            window: self.window,
+            timeout: millis,
+        }
+    }
+
+    /// Replaces the page with `content`, as `WebViewBuilder::content` does when
+    /// the window is created.
+    pub fn navigate<S: Into<String>>(&mut self, content: Content<'a, S>) -> Result<()> {
+        let window = self.window;
+        // Cached ranges belong to the previous content.
+        self.internal.response = None;
+        let internal = self.internal.as_mut() as *mut InternalData as *mut c_void;
 
         ffi_result(unsafe {
             match content {
@@ -114,40 +412,102 @@ impl<'a> WebView<'a> {
                     ((), result)
                 }
                 Content::Dir(dir, source) => {
-                    webview.internal.dir = Some(dir);
+                    self.internal.dir = Some(dir);
+                    self.internal.dir_path = None;
+                    let source = CString::new(source.into())?;
+                    let result =
+                        webview_navigate_with_streamresolver(window, internal, source.as_ptr());
+                    ((), result)
+                }
+                Content::DirPath(path, source) => {
+                    self.internal.dir = None;
+                    self.internal.dir_path = Some(path);
                     let source = CString::new(source.into())?;
                     let result =
                         webview_navigate_with_streamresolver(window, internal, source.as_ptr());
                     ((), result)
                 }
+                Content::Bytes { data, mime } => {
+                    self.internal.bytes = Some((data, CString::new(mime.into())?));
+                    let source = CString::new(BYTES_SOURCE)?;
+                    let result =
+                        webview_navigate_with_streamresolver(window, internal, source.as_ptr());
+                    ((), result)
+                }
             }
-        })?;
-
-        Ok(webview)
+        })
     }
 
-    pub fn dispatcher(&mut self) -> Dispatcher<'a> {
-        Dispatcher {
-            phantom: PhantomData,
-            window: self.window,
-            webview: self as *mut WebView as *mut c_void,
+    /// Navigates to `url` with the given method, headers and body, for example
+    /// to pass an authorization header or submit a form.
+    pub fn navigate_with_request(
+        &mut self,
+        url: &str,
+        method: HttpMethod,
+        headers: &[(String, String)],
+        body: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let url = CString::new(url)?;
+        let method = CString::new(method.as_str())?;
+        let mut names = Vec::with_capacity(headers.len());
+        let mut values = Vec::with_capacity(headers.len());
+        for (name, value) in headers {
+            names.push(CString::new(name.as_str())?);
+            values.push(CString::new(value.as_str())?);
         }
+        let name_ptrs: Vec<*const c_char> = names.iter().map(|name| name.as_ptr()).collect();
+        let value_ptrs: Vec<*const c_char> = values.iter().map(|value| value.as_ptr()).collect();
+        let (body_ptr, body_len) = match body {
+            Some(ref body) => (body.as_ptr(), body.len()),
+            None => (ptr::null(), 0),
+        };
+        let internal = self.internal.as_mut() as *mut InternalData as *mut c_void;
+
+        ffi_result(unsafe {
+            let result = webview_navigate_with_request(
+                self.window,
+                internal,
+                url.as_ptr(),
+                method.as_ptr(),
+                name_ptrs.as_ptr(),
+                value_ptrs.as_ptr(),
+                headers.len(),
+                body_ptr,
+                body_len,
+            );
+            ((), result)
+        })
     }
 
-    pub fn poll_iter(&self) -> EventIterator<'a> {
-        EventIterator {
-            phantom: PhantomData,
-            window: self.window,
-            blocking: false,
+    /// Pumps events until the window closes or `handler` returns
+    /// `ControlFlow::Exit`. The handler gets the web view back so it can
+    /// script it in response. `Event::Quit` ends the loop without reaching it.
+    pub fn run<F>(&mut self, mut handler: F)
+    where
+        F: FnMut(&mut WebView<'a>, Event) -> ControlFlow,
+    {
+        loop {
+            let event = match self.wait_iter().next() {
+                Some(Event::Quit) => return,
+                Some(event) => event,
+                None => continue,
+            };
+            if handler(self, event) == ControlFlow::Exit {
+                return;
+            }
         }
     }
 
-    pub fn wait_iter(&self) -> EventIterator<'a> {
-        EventIterator {
-            phantom: PhantomData,
-            window: self.window,
-            blocking: true,
-        }
+    /// Routes requests for `/<scheme>/<path>` made from content served through the
+    /// stream resolver to `handler`, which is passed `<path>`. Returning `None`
+    /// falls back to the bundled `Dir`, if any.
+    pub fn register_protocol<F>(&mut self, scheme: &str, handler: F)
+    where
+        F: FnMut(&str) -> Option<Resource> + 'a,
+    {
+        self.internal
+            .protocols
+            .insert(scheme.to_owned(), Box::new(handler));
     }
 
     pub fn eval_script(&mut self, script: &str) -> Result<String> {
@@ -167,13 +527,53 @@ impl<'a> WebView<'a> {
         Ok(value)
     }
 
-    pub fn inject_css(&mut self, css: &str) -> Result<()> {
+    /// Settles the promise returned to the page for request `id` with `value`.
+    pub fn reply(&mut self, id: u64, value: &str) -> Result<()> {
+        self.eval_script(&format!(
+            "window.__webview_rpc.resolve({}, {}); ''",
+            id,
+            js_string(value)
+        ))?;
+        Ok(())
+    }
+
+    /// The native window, or null if it could not be queried.
+    pub fn hwnd(&self) -> *mut c_void {
+        ffi_result(unsafe {
+            let mut hwnd = ptr::null_mut();
+            let result = webview_get_hwnd(self.window, &mut hwnd);
+            (hwnd, result)
+        })
+        .unwrap_or(ptr::null_mut())
+    }
+
+    pub fn set_title(&mut self, title: &str) -> Result<()> {
+        let title = CString::new(title)?;
+        ffi_result(unsafe { ((), webview_set_title(self.window, title.as_ptr())) })
+    }
+
+    /// Adds `css` to the page. The style sheet stays until the handle is
+    /// passed to `remove_css`, even if the handle is dropped.
+    pub fn inject_css(&mut self, css: &str) -> Result<StyleHandle> {
         let css = CString::new(css)?;
+        let id = self.next_style_id;
+        self.next_style_id += 1;
         ffi_result(unsafe {
-            let result = webview_inject_css(self.window, css.as_ptr());
-            ((), result)
+            let result = webview_inject_css(self.window, css.as_ptr(), id);
+            (StyleHandle(id), result)
         })
     }
+
+    pub fn remove_css(&mut self, handle: StyleHandle) -> Result<()> {
+        self.eval_script(&format!(
+            "(function () {{ \
+                 var style = document.querySelector('style[data-webview-style=\"{}\"]'); \
+                 if (style) style.parentNode.removeChild(style); \
+             }})(); ''",
+            handle.0
+        ))?;
+        Ok(())
+    }
 }
 
 impl<'a> Drop for WebView<'a> {
@@ -182,7 +582,17 @@ impl<'a> Drop for WebView<'a> {
     }
 }
 
-fn ffi_result<T>(result: (T, i32)) -> Result<T> {
+#[cfg(feature = "raw-window-handle")]
+unsafe impl<'a> raw_window_handle::HasRawWindowHandle for WebView<'a> {
+    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
+        raw_window_handle::RawWindowHandle::Windows(raw_window_handle::windows::WindowsHandle {
+            hwnd: self.hwnd(),
+            ..raw_window_handle::windows::WindowsHandle::empty()
+        })
+    }
+}
+
+pub(crate) fn ffi_result<T>(result: (T, i32)) -> Result<T> {
     match result {
         (value, 0) => Ok(value),
         (_, code) => {
@@ -207,13 +617,33 @@ impl<'a> Dispatcher<'a> {
     where
         F: FnMut(&mut WebView) + 'a,
     {
-        ffi_result(unsafe {
-            let info_ptr = Box::into_raw(Box::new(CallbackInfo {
-                callback: Box::new(callback),
-            }));
+        let info_ptr = Box::into_raw(Box::new(CallbackInfo {
+            callback: Box::new(callback),
+        }));
+        let result = ffi_result(unsafe {
             let result = webview_dispatch(self.window, self.webview, info_ptr as *mut c_void);
             ((), result)
-        })
+        });
+        if result.is_err() {
+            let _ = unsafe { Box::from_raw(info_ptr) };
+        }
+        result
+    }
+
+    /// Evaluates `script` on the UI thread and passes the result to `callback`.
+    /// If the window goes away before the script runs, `callback` receives
+    /// `Error::Closed`.
+    pub fn eval_script<F>(&mut self, script: &str, callback: F)
+    where
+        F: FnOnce(Result<String>) + 'a,
+    {
+        let script = script.to_owned();
+        let mut completion = Completion(Some(callback));
+        let _ = self.dispatch(move |webview| {
+            if let Some(callback) = completion.0.take() {
+                callback(webview.eval_script(&script));
+            }
+        });
     }
 }
 
@@ -233,8 +663,19 @@ impl<'a> Iterator for EventIterator<'a> {
     fn next(&mut self) -> Option<Event> {
         let mut event: u32 = EventType_None;
         let mut data: *mut c_char = ptr::null_mut();
-
-        unsafe { webview_loop(self.window, self.blocking, &mut event, &mut data) };
+        let mut width = 0;
+        let mut height = 0;
+
+        unsafe {
+            webview_loop(
+                self.window,
+                self.timeout,
+                &mut event,
+                &mut data,
+                &mut width,
+                &mut height,
+            )
+        };
 
         match event {
             EventType_Quit => Some(Event::Quit),
@@ -244,6 +685,14 @@ impl<'a> Iterator for EventIterator<'a> {
                 unsafe { webview_string_free(data) };
                 Some(Event::ScriptNotify(response))
             }
+            EventType_Resized => Some(Event::Resized(width, height)),
+            EventType_FileDrop => {
+                let paths = unsafe { CStr::from_ptr(data).to_string_lossy().to_string() };
+                unsafe { webview_string_free(data) };
+                Some(Event::FileDrop(
+                    paths.split('\n').map(PathBuf::from).collect(),
+                ))
+            }
             _ => None,
         }
     }
@@ -261,49 +710,146 @@ where
 {
     let mut webview = WebView::new(title, content, size, resizable)?;
 
-    'running: loop {
-        for event in webview.wait_iter() {
-            match event {
-                Event::Quit => {
-                    break 'running;
-                }
-                event => {
-                    callback(&mut webview, event);
-                }
-            }
-        }
-    }
+    webview.run(|webview, event| {
+        callback(webview, event);
+        ControlFlow::Continue
+    });
 
     Ok(())
 }
 
+// Points `content` at the requested slice of `body`. A `max_length` of zero
+// only reports the size.
+pub(crate) unsafe fn set_range(
+    body: &[u8],
+    offset: u64,
+    max_length: u64,
+    content: *mut *const u8,
+    length: *mut usize,
+    total_length: *mut u64,
+) {
+    let start = offset.min(body.len() as u64) as usize;
+    let end = offset.saturating_add(max_length).min(body.len() as u64) as usize;
+    *content = body[start..].as_ptr();
+    *length = end - start;
+    *total_length = body.len() as u64;
+}
+
 #[no_mangle]
 pub extern "C" fn webview_get_content(
     webview_ptr: *mut c_void,
     source: *const c_char,
+    offset: u64,
+    max_length: u64,
     content: *mut *const u8,
     length: *mut usize,
+    total_length: *mut u64,
+    mime: *mut *const c_char,
 ) -> bool {
     let internal = unsafe { (webview_ptr as *mut InternalData).as_mut().unwrap() };
     unsafe {
         *content = ptr::null();
         *length = 0;
+        *total_length = 0;
+        *mime = ptr::null();
+    };
+
+    // Called from C++, so nothing here may unwind; a path that isn't UTF-8
+    // is looked up lossily instead.
+    let source = unsafe { CStr::from_ptr(source) }.to_string_lossy();
+    let source = &*source;
+
+    if source == BYTES_SOURCE {
+        if let Some((ref body, ref bytes_mime)) = internal.bytes {
+            unsafe {
+                set_range(body, offset, max_length, content, length, total_length);
+                *mime = bytes_mime.as_ptr();
+            };
+
+            return true;
+        }
+    }
+
+    let relative = source.trim_start_matches('/');
+    let (scheme, rest) = match relative.find('/') {
+        Some(index) => (&relative[..index], &relative[index + 1..]),
+        None => (relative, ""),
     };
+    if let Some(handler) = internal.protocols.get_mut(scheme) {
+        // Handlers run once per resource; later range reads of the same path
+        // are served from the kept response.
+        let cached = match internal.response {
+            Some((ref path, _, _)) => max_length > 0 && path == source,
+            None => false,
+        };
+        if !cached {
+            // A panicking handler is treated as not having the resource.
+            let resource = panic::catch_unwind(AssertUnwindSafe(|| handler(rest)));
+            internal.response = resource.ok().and_then(|resource| resource).map(|resource| {
+                let resource_mime = CString::new(resource.mime).unwrap_or_default();
+                (source.to_owned(), resource.data, resource_mime)
+            });
+        }
+        if let Some((_, ref body, ref resource_mime)) = internal.response {
+            unsafe {
+                set_range(body, offset, max_length, content, length, total_length);
+                *mime = resource_mime.as_ptr();
+            };
+
+            return true;
+        }
+    }
+
+    if let Some(ref root) = internal.dir_path {
+        let cached = match internal.response {
+            Some((ref path, _, _)) => max_length > 0 && path == source,
+            None => false,
+        };
+        if !cached {
+            internal.response = read_dev_file(
+                root,
+                source,
+                internal.spa_fallback,
+                internal.reload_on_change,
+            );
+        }
+        if let Some((_, ref body, ref file_mime)) = internal.response {
+            unsafe {
+                set_range(body, offset, max_length, content, length, total_length);
+                *mime = file_mime.as_ptr();
+            };
+
+            return true;
+        }
+    }
 
     if let Some(ref dir) = internal.dir {
-        let source = unsafe { CStr::from_ptr(source).to_str().unwrap() };
         let path = Path::new(source);
         let path = if path.starts_with("/") {
             path.strip_prefix("/").unwrap()
         } else {
             path
         };
-        if dir.contains(path) {
-            let file = dir.get_file(path.to_str().unwrap()).unwrap();
-            let body = file.contents();
+        // Client-side routes such as `/dashboard` have no file behind them, so
+        // a single-page app gets its `index.html` for any extensionless path.
+        let file = dir.get_file(path.to_str().unwrap()).or_else(|| {
+            if internal.spa_fallback && path.extension().is_none() {
+                dir.get_file(SPA_INDEX)
+            } else {
+                None
+            }
+        });
+        if let Some(file) = file {
             unsafe {
-                *content = body.as_ptr();
-                *length = body.len();
+                set_range(
+                    file.contents(),
+                    offset,
+                    max_length,
+                    content,
+                    length,
+                    total_length,
+                );
+                *mime = mime_type(file.path()).as_ptr() as *const c_char;
             };
 
             return true;
@@ -313,9 +859,76 @@ pub extern "C" fn webview_get_content(
     false
 }
 
+fn read_dev_file(
+    root: &Path,
+    source: &str,
+    spa_fallback: bool,
+    reload_on_change: bool,
+) -> Option<(String, Vec<u8>, CString)> {
+    if reload_on_change && source == VERSION_SOURCE {
+        let version = dir_version(root).to_string().into_bytes();
+        return Some((
+            source.to_owned(),
+            version,
+            CString::new("text/plain").unwrap(),
+        ));
+    }
+
+    // Only plain names, so requests can't climb out of `root`.
+    let relative = Path::new(source.trim_start_matches('/'));
+    let outside = relative.components().any(|component| match component {
+        Component::Normal(_) => false,
+        _ => true,
+    });
+    if outside {
+        return None;
+    }
+
+    let mut path = root.join(relative);
+    if !path.is_file() {
+        if spa_fallback && relative.extension().is_none() {
+            path = root.join(SPA_INDEX);
+        } else {
+            return None;
+        }
+    }
+
+    let mut data = fs::read(&path).ok()?;
+    let file_mime = mime_type(&path);
+    if reload_on_change && file_mime == b"text/html\0" {
+        data.extend_from_slice(RELOAD_SCRIPT.as_bytes());
+    }
+    let file_mime = CStr::from_bytes_with_nul(file_mime).unwrap().to_owned();
+    Some((source.to_owned(), data, file_mime))
+}
+
+// Changes whenever a file under `dir` is added, removed or modified.
+fn dir_version(dir: &Path) -> u64 {
+    let mut version = 0u64;
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                version = version.wrapping_add(dir_version(&path));
+            } else if let Ok(modified) = entry.metadata().and_then(|meta| meta.modified()) {
+                let nanos = modified
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |since| since.as_nanos() as u64);
+                version = version.wrapping_add(nanos).wrapping_add(1);
+            }
+        }
+    }
+    version
+}
+
 #[no_mangle]
 pub extern "C" fn webview_dispatch_callback(webview_ptr: *mut c_void, info_ptr: *mut c_void) {
     let mut webview = unsafe { (webview_ptr as *mut WebView).as_mut().unwrap() };
     let mut info = unsafe { Box::from_raw(info_ptr as *mut CallbackInfo) };
     (info.callback)(&mut webview);
 }
+
+#[no_mangle]
+pub extern "C" fn webview_dispatch_cancel(_webview_ptr: *mut c_void, info_ptr: *mut c_void) {
+    let _ = unsafe { Box::from_raw(info_ptr as *mut CallbackInfo) };
+}
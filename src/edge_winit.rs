@@ -1,4 +1,5 @@
-use crate::edge::{self, Control, Process};
+use crate::edge::{self, Color, Control, HostWindowStyle, Process};
+use crate::error::Error;
 
 use winapi::shared::windef::HWND;
 
@@ -18,8 +19,9 @@ pub fn new_control<F>(
     hwnd_type: HwndType,
     position: Option<LogicalPosition>,
     size: Option<LogicalSize>,
+    background: Option<Color>,
     callback: Option<F>,
-) -> Result<Control, String>
+) -> Result<Control, Error>
 where
     F: FnOnce(Control) + 'static,
 {
@@ -42,12 +44,16 @@ where
         })
         .to_physical(dpi_factor)
         .into();
-    process
-        .create_control(
-            hwnd_type,
-            position,
-            (size.0 as i32, size.1 as i32),
-            callback,
-        )
-        .map_err(|err| err.to_string())
+    let control = process.create_control(
+        hwnd_type,
+        position,
+        (size.0 as i32, size.1 as i32),
+        HostWindowStyle {
+            background,
+            ..HostWindowStyle::default()
+        },
+        callback,
+    )?;
+    control.set_dpi_factor(dpi_factor);
+    Ok(control)
 }
@@ -1,18 +1,36 @@
+use std::ffi::NulError;
 use std::fmt;
 use std::io;
-use winrt;
 
 #[derive(Debug)]
 pub enum Error {
     Io(io::Error),
+    #[cfg(all(windows, feature = "edgehtml"))]
     Rt(winrt::Error),
+    Null(NulError),
+    Runtime(i32, String),
+    NotCreated,
+    Closed,
+    TooLarge(usize),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::Io(ref err) => write!(f, "I/O error: {}", err),
+            #[cfg(all(windows, feature = "edgehtml"))]
             Error::Rt(ref err) => write!(f, "WinRT error: {:?}", err),
+            Error::Null(ref err) => write!(f, "Null error: {}", err),
+            Error::Runtime(code, ref message) => {
+                write!(f, "Windows Runtime error 0x{:08x}: \"{}\"", code, message)
+            }
+            Error::NotCreated => write!(f, "Web view control has not been created yet"),
+            Error::Closed => write!(f, "Web view was closed"),
+            Error::TooLarge(len) => write!(
+                f,
+                "{} bytes is too large for a data URI; use a stream resolver",
+                len
+            ),
         }
     }
 }
@@ -21,7 +39,8 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
             Error::Io(ref err) => Some(err),
-            Error::Rt(_) => None,
+            Error::Null(ref err) => Some(err),
+            _ => None,
         }
     }
 }
@@ -32,8 +51,15 @@ impl From<io::Error> for Error {
     }
 }
 
+#[cfg(all(windows, feature = "edgehtml"))]
 impl From<winrt::Error> for Error {
     fn from(error: winrt::Error) -> Error {
         Error::Rt(error)
     }
 }
+
+impl From<NulError> for Error {
+    fn from(error: NulError) -> Error {
+        Error::Null(error)
+    }
+}
@@ -1,10 +1,18 @@
 pub mod error;
 pub use error::Error;
 
+#[cfg(all(windows, feature = "edgehtml"))]
 #[macro_use]
 extern crate lazy_static;
 
+pub mod webview;
+pub use webview::WebView;
+
 #[cfg(all(windows, feature = "edgehtml"))]
 pub mod edge;
+#[cfg(windows)]
 pub mod edge_manual;
+#[cfg(all(windows, feature = "edgehtml", feature = "winit"))]
 pub mod edge_winit;
+#[cfg(feature = "mock")]
+pub mod mock;
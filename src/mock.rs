@@ -0,0 +1,154 @@
+//! Stand-ins for the native types that record what was asked of them instead
+//! of driving a web view, so code using this crate can be tested anywhere.
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::error::Error;
+use crate::webview::WebView;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Call {
+    Navigate(String),
+    NavigateToString(String),
+    NavigateToFile(PathBuf),
+    Eval(String),
+    Load(String),
+    SetHtml(String),
+    Title(String),
+}
+
+#[derive(Clone, Default)]
+struct Recorder {
+    calls: Rc<RefCell<Vec<Call>>>,
+}
+
+impl Recorder {
+    fn record(&self, call: Call) {
+        self.calls.borrow_mut().push(call);
+    }
+
+    fn calls(&self) -> Vec<Call> {
+        self.calls.borrow().clone()
+    }
+
+    fn clear(&self) {
+        self.calls.borrow_mut().clear();
+    }
+}
+
+/// Records calls in place of `edge::Control`. Clones share one record.
+#[derive(Clone, Default)]
+pub struct MockControl {
+    recorder: Recorder,
+}
+
+impl MockControl {
+    pub fn new() -> MockControl {
+        MockControl::default()
+    }
+
+    pub fn eval(&self, script: &str) -> Result<(), Error> {
+        self.recorder.record(Call::Eval(script.to_owned()));
+        Ok(())
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.recorder.calls()
+    }
+
+    pub fn clear(&self) {
+        self.recorder.clear()
+    }
+}
+
+impl WebView for MockControl {
+    type Error = Error;
+
+    fn navigate(&self, url: &str) -> Result<(), Error> {
+        self.recorder.record(Call::Navigate(url.to_owned()));
+        Ok(())
+    }
+
+    fn navigate_to_string(&self, html: &str) -> Result<(), Error> {
+        self.recorder
+            .record(Call::NavigateToString(html.to_owned()));
+        Ok(())
+    }
+
+    /// Records the path without reading the file.
+    fn navigate_to_file(&self, path: &Path) -> Result<(), Error> {
+        self.recorder.record(Call::NavigateToFile(path.to_owned()));
+        Ok(())
+    }
+}
+
+/// Records calls in place of `webview_api::Window`. Clones share one record.
+#[derive(Clone, Default)]
+pub struct MockWindow {
+    recorder: Recorder,
+}
+
+impl MockWindow {
+    pub fn new() -> MockWindow {
+        MockWindow::default()
+    }
+
+    pub fn eval<I: Into<String>>(&self, s: I) {
+        self.recorder.record(Call::Eval(s.into()));
+    }
+
+    pub fn load<I: Into<String>>(&self, s: I) {
+        self.recorder.record(Call::Load(s.into()));
+    }
+
+    pub fn set_html<I: Into<String>>(&self, html: I) {
+        self.recorder.record(Call::SetHtml(html.into()));
+    }
+
+    pub fn title<I: Into<String>>(&self, s: I) {
+        self.recorder.record(Call::Title(s.into()));
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.recorder.calls()
+    }
+
+    pub fn clear(&self) {
+        self.recorder.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands in for application code written against the trait.
+    fn open_home<W: WebView>(view: &W) -> Result<(), W::Error> {
+        view.navigate("https://example.com/")?;
+        view.navigate_to_string("<p>offline</p>")?;
+        view.navigate_to_file(Path::new("index.html"))
+    }
+
+    #[test]
+    fn control_records_web_view_calls_in_order() {
+        let control = MockControl::new();
+        let clone = control.clone();
+        open_home(&control).unwrap();
+        clone.eval("1 + 1").unwrap();
+
+        assert_eq!(
+            control.calls(),
+            vec![
+                Call::Navigate("https://example.com/".to_owned()),
+                Call::NavigateToString("<p>offline</p>".to_owned()),
+                Call::NavigateToFile(PathBuf::from("index.html")),
+                Call::Eval("1 + 1".to_owned()),
+            ]
+        );
+
+        clone.clear();
+        assert!(control.calls().is_empty());
+    }
+}
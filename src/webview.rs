@@ -0,0 +1,8 @@
+use std::path::Path;
+
+pub trait WebView {
+    type Error;
+    fn navigate(&self, url: &str) -> Result<(), Self::Error>;
+    fn navigate_to_string(&self, html: &str) -> Result<(), Self::Error>;
+    fn navigate_to_file(&self, path: &Path) -> Result<(), Self::Error>;
+}
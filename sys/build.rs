@@ -11,6 +11,7 @@ fn main() {
         .whitelist_type("EventType")
         .whitelist_function("webview_new")
         .whitelist_function("webview_navigate")
+        .whitelist_function("webview_navigate_with_request")
         .whitelist_function("webview_navigate_with_streamresolver")
         .whitelist_function("webview_loop")
         .whitelist_function("webview_dispatch")
@@ -18,6 +19,11 @@ fn main() {
         .whitelist_function("webview_string_free")
         .whitelist_function("webview_eval_script")
         .whitelist_function("webview_inject_css")
+        .whitelist_function("webview_set_title")
+        .whitelist_function("webview_get_hwnd")
+        .whitelist_function("webview_set_min_size")
+        .whitelist_function("webview_open_dev_tools")
+        .whitelist_function("webview_new_stream_resolver")
         .whitelist_function("webview_get_error_message")
         .generate()
         .expect("Unable to generate bindings");
@@ -43,6 +49,7 @@ fn main() {
         "gdi32",
         "winspool",
         "comdlg32",
+        "shell32",
         "advapi32",
         "ole32",
         "oleaut32",
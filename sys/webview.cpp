@@ -2,20 +2,25 @@
 #include "common.hpp"
 #include "webview.hpp"
 #include <string>
+#include <algorithm>
 #include <deque>
+#include <shellapi.h>
 #include <functional>
 #include <hstring.h>
 #include <winrt/Windows.Foundation.h>
 #include <winrt/Windows.Storage.h>
 #include <winrt/Windows.Storage.Streams.h>
+#include <winrt/Windows.Web.Http.h>
+#include <winrt/Windows.Web.Http.Headers.h>
 #include <winrt/Windows.Web.UI.Interop.h>
 #include <winrt/Windows.Storage.Streams.h>
 #include <winrt/Windows.Security.Cryptography.h>
 
 extern "C"
 {
-    extern bool webview_get_content(void *webview, const char *path, const uint8_t **content, size_t *length);
+    extern bool webview_get_content(void *webview, const char *path, uint64_t offset, uint64_t max_length, const uint8_t **content, size_t *length, uint64_t *total_length, const char **mime);
     extern void webview_dispatch_callback(void *webview, void *callback);
+    extern void webview_dispatch_cancel(void *webview, void *callback);
 }
 
 using namespace winrt;
@@ -32,29 +37,192 @@ using namespace Windows::Web;
 // UriToStreamResolver class
 namespace
 {
+class UriToStreamResolver;
+
+// A read-only random access stream over a resolved resource. WebView answers
+// Range requests, such as those of a seeking <video>, by seeking the stream it
+// was given, so each read asks the content callback for just that slice
+// instead of copying the whole resource up front.
+class ContentStream : public winrt::implements<ContentStream, IRandomAccessStream, IInputStream, IOutputStream, IClosable, IContentTypeProvider>
+{
+public:
+    ContentStream(winrt::com_ptr<UriToStreamResolver> resolver, std::string path, uint64_t size, winrt::hstring contentType, uint64_t position = 0)
+        : m_resolver(std::move(resolver)), m_path(std::move(path)), m_size(size), m_contentType(contentType), m_position(position)
+    {
+    }
+
+    IAsyncOperationWithProgress<IBuffer, uint32_t> ReadAsync(IBuffer buffer, uint32_t count, InputStreamOptions options);
+
+    IAsyncOperationWithProgress<uint32_t, uint32_t> WriteAsync(IBuffer) const
+    {
+        throw winrt::hresult_not_implemented();
+    }
+
+    IAsyncOperation<bool> FlushAsync() const
+    {
+        throw winrt::hresult_not_implemented();
+    }
+
+    uint64_t Size() const
+    {
+        return m_size;
+    }
+
+    void Size(uint64_t) const
+    {
+        throw winrt::hresult_not_implemented();
+    }
+
+    IInputStream GetInputStreamAt(uint64_t position) const
+    {
+        return winrt::make<ContentStream>(m_resolver, m_path, m_size, m_contentType, position);
+    }
+
+    IOutputStream GetOutputStreamAt(uint64_t) const
+    {
+        throw winrt::hresult_not_implemented();
+    }
+
+    uint64_t Position() const
+    {
+        return m_position;
+    }
+
+    void Seek(uint64_t position)
+    {
+        m_position = position;
+    }
+
+    IRandomAccessStream CloneStream() const
+    {
+        return winrt::make<ContentStream>(m_resolver, m_path, m_size, m_contentType);
+    }
+
+    bool CanRead() const
+    {
+        return true;
+    }
+
+    bool CanWrite() const
+    {
+        return false;
+    }
+
+    winrt::hstring ContentType() const
+    {
+        return m_contentType;
+    }
+
+    void Close() const
+    {
+    }
+
+private:
+    const winrt::com_ptr<UriToStreamResolver> m_resolver;
+    const std::string m_path;
+    const uint64_t m_size;
+    const winrt::hstring m_contentType;
+    uint64_t m_position;
+};
+
 class UriToStreamResolver : public winrt::implements<UriToStreamResolver, IUriToStreamResolver>
 {
 public:
-    UriToStreamResolver(void *webview) : m_webview(webview)
+    UriToStreamResolver(void *context, webview_content_fn content, void (*freeContext)(void *) = nullptr)
+        : m_context(context), m_content(content), m_freeContext(freeContext)
     {
     }
 
-    IAsyncOperation<IInputStream> UriToStreamAsync(Uri uri) const
+    // The last reference may be released on a WebView thread, so the context
+    // is freed back on the apartment that created the resolver.
+    ~UriToStreamResolver()
     {
-        Uri localUri = Uri(uri.Path());
-        auto fOp = StorageFile::GetFileFromPathAsync(localUri.AbsoluteUri());
+        if (m_freeContext != nullptr)
+        {
+            FreeContextAsync(m_apartment, m_context, m_freeContext);
+        }
+    }
 
-        StorageFile f = fOp.get();
+    // WebView calls this on one of its own threads; the lookup moves to the
+    // apartment that created the resolver, like every other content call.
+    IAsyncOperation<IInputStream> UriToStreamAsync(Uri uri)
+    {
+        auto self = get_strong();
+        std::string path = winrt::to_string(uri.Path());
+        co_await m_apartment;
 
-        auto sOp = f.OpenAsync(FileAccessMode::Read);
-        IRandomAccessStream stream = sOp.get();
+        const uint8_t *content = nullptr;
+        size_t length = 0;
+        uint64_t totalLength = 0;
+        const char *mime = nullptr;
 
-        co_return stream.GetInputStreamAt(0);
+        // Asking for no bytes only looks the resource up, for its size and type.
+        if (!m_content(m_context, path.c_str(), 0, 0, &content, &length, &totalLength, &mime))
+        {
+            winrt::throw_hresult(HRESULT_FROM_WIN32(ERROR_FILE_NOT_FOUND));
+        }
+
+        const winrt::hstring contentType = mime != nullptr ? winrt::to_hstring(mime) : winrt::hstring();
+
+        co_return winrt::make<ContentStream>(self, std::move(path), totalLength, contentType);
+    }
+
+    // Copies at most `count` bytes from `offset` into `buffer`. The callback
+    // is only ever invoked on the apartment that created the resolver.
+    IAsyncOperation<IBuffer> ReadRangeAsync(std::string path, uint64_t offset, uint32_t count, IBuffer buffer)
+    {
+        auto self = get_strong();
+        co_await m_apartment;
+
+        const uint8_t *content = nullptr;
+        size_t length = 0;
+        uint64_t totalLength = 0;
+        const char *mime = nullptr;
+
+        if (!m_content(m_context, path.c_str(), offset, count, &content, &length, &totalLength, &mime))
+        {
+            winrt::throw_hresult(HRESULT_FROM_WIN32(ERROR_FILE_NOT_FOUND));
+        }
+
+        // The content is only guaranteed to stay alive for the duration of the call.
+        length = std::min<size_t>(length, std::min(count, buffer.Capacity()));
+        memcpy(buffer.data(), content, length);
+        buffer.Length(static_cast<uint32_t>(length));
+
+        co_return buffer;
     }
 
 private:
-    void *const m_webview;
+    static winrt::fire_and_forget FreeContextAsync(winrt::apartment_context apartment, void *context, void (*freeContext)(void *))
+    {
+        try
+        {
+            co_await apartment;
+        }
+        catch (hresult_error const &)
+        {
+            // The apartment is gone, and with it anything the context refers to.
+            co_return;
+        }
+        freeContext(context);
+    }
+
+    void *const m_context;
+    const webview_content_fn m_content;
+    void (*const m_freeContext)(void *);
+    winrt::apartment_context m_apartment;
 };
+
+IAsyncOperationWithProgress<IBuffer, uint32_t> ContentStream::ReadAsync(IBuffer buffer, uint32_t count, InputStreamOptions)
+{
+    auto self = get_strong();
+    const uint64_t offset = m_position;
+
+    IBuffer read = co_await m_resolver->ReadRangeAsync(m_path, offset, count, buffer);
+    m_position = offset + read.Length();
+
+    co_return read;
+}
 } // namespace
 namespace
 {
@@ -62,6 +230,8 @@ struct EventInfo
 {
     EventType type;
     std::string data;
+    int32_t width = 0;
+    int32_t height = 0;
 };
 
 std::wstring WideStringFromString(const std::string &narrow)
@@ -142,6 +312,8 @@ public:
             hInstance,
             reinterpret_cast<LPVOID>(this)));
 
+        ::DragAcceptFiles(hwnd, TRUE);
+
         m_process = WebViewControlProcess();
 
         const Rect bounds = _GetBounds();
@@ -151,6 +323,29 @@ public:
         m_control.Settings().IsScriptNotifyAllowed(true);
         m_control.IsVisible(true);
 
+        // `webviewRequest(body)` sends "__webview_rpc:<id>:<body>" through
+        // ScriptNotify and returns a promise settled by `WebView::reply`.
+        static constexpr char s_requestScript[] =
+            "(function () {"
+            "    var pending = {};"
+            "    var next = 0;"
+            "    window.__webview_rpc = {"
+            "        resolve: function (id, value) {"
+            "            var p = pending[id];"
+            "            delete pending[id];"
+            "            if (p) p(value);"
+            "        }"
+            "    };"
+            "    window.webviewRequest = function (body) {"
+            "        var id = next++;"
+            "        return new Promise(function (resolve) {"
+            "            pending[id] = resolve;"
+            "            window.external.notify('__webview_rpc:' + id + ':' + body);"
+            "        });"
+            "    };"
+            "})();";
+        m_control.AddInitializeScript(winrt::to_hstring(s_requestScript));
+
         m_domContentLoadedRevoker = m_control.DOMContentLoaded(winrt::auto_revoke, {this, &Window::OnDOMContentLoaded});
         m_scriptNotifyRevoker = m_control.ScriptNotify(winrt::auto_revoke, {this, &Window::OnScriptNotify});
 
@@ -179,12 +374,49 @@ public:
         }
     }
 
+    void NavigateWithRequest(
+        void *webview,
+        const char *url,
+        const char *method,
+        const char *const *headerNames,
+        const char *const *headerValues,
+        size_t headerCount,
+        const uint8_t *body,
+        size_t bodyLength)
+    {
+        m_owner = webview;
+
+        Http::HttpRequestMessage request(Http::HttpMethod(winrt::to_hstring(method)), Uri(winrt::to_hstring(url)));
+
+        Http::IHttpContent content = nullptr;
+        if (body != nullptr)
+        {
+            content = Http::HttpBufferContent(
+                CryptographicBuffer::CreateFromByteArray(winrt::array_view<const uint8_t>(body, body + bodyLength)));
+            request.Content(content);
+        }
+
+        // Content headers such as Content-Type are rejected on the request
+        // itself and belong to the body instead.
+        for (size_t i = 0; i < headerCount; ++i)
+        {
+            const auto name = winrt::to_hstring(headerNames[i]);
+            const auto value = winrt::to_hstring(headerValues[i]);
+            if (!request.Headers().TryAppendWithoutValidation(name, value) && content)
+            {
+                content.Headers().TryAppendWithoutValidation(name, value);
+            }
+        }
+
+        m_control.NavigateWithHttpRequestMessage(request);
+    }
+
     void NavigateWithStreamResolver(void *webview, const std::string &path)
     {
         m_owner = webview;
 
         auto source = m_control.BuildLocalStreamUri(winrt::to_hstring("WebView"), winrt::to_hstring(path));
-        auto resolver = winrt::make_self<UriToStreamResolver>(webview);
+        auto resolver = winrt::make_self<UriToStreamResolver>(webview, webview_get_content);
         m_control.NavigateToLocalStreamUri(source, resolver.as<IUriToStreamResolver>());
     }
 
@@ -195,14 +427,15 @@ public:
         return winrt::to_string(value);
     }
 
-    void InjectCss(const std::string &css)
+    void InjectCss(const std::string &css, uint32_t id)
     {
         if (!m_injectCssFunctionInitialized)
         {
             static constexpr char s_injectCss[] =
-                "window.__webview_injectCss = function __webview_injectCss(css) {"
+                "window.__webview_injectCss = function __webview_injectCss(css, id) {"
                 "    const style = document.createElement('style');"
                 "    style.type = 'text/css';"
+                "    style.setAttribute('data-webview-style', id);"
                 "    style.innerHTML = css;"
                 "    document.head.appendChild(style);"
                 "};";
@@ -212,11 +445,13 @@ public:
             m_injectCssFunctionInitialized = true;
         }
 
-        auto op = (m_control.InvokeScriptAsync(winrt::to_hstring("__webview_injectCss"), {winrt::to_hstring(css)}));
+        auto op = (m_control.InvokeScriptAsync(winrt::to_hstring("__webview_injectCss"), {winrt::to_hstring(css), winrt::to_hstring(id)}));
         AwaitAsyncOperation(op);
     }
 
-    EventInfo Loop(const bool blocking)
+    // Waits up to `timeout` milliseconds for an event. Zero only handles the
+    // messages already queued and INFINITE waits for as long as it takes.
+    EventInfo Loop(const uint32_t timeout)
     {
         if (m_events.size() > 0)
         {
@@ -225,7 +460,7 @@ public:
             return event;
         }
 
-        if (blocking)
+        if (timeout == INFINITE)
         {
             MSG msg;
             while (::GetMessage(&msg, nullptr, 0, 0))
@@ -244,7 +479,7 @@ public:
             // WM_QUIT
             return {EventType::Quit, ""};
         }
-        else
+        else if (timeout == 0)
         {
             MSG msg;
             if (::PeekMessage(&msg, nullptr, 0, 0, PM_REMOVE))
@@ -268,12 +503,76 @@ public:
 
             return {EventType::None, ""};
         }
+        else
+        {
+            const ULONGLONG deadline = ::GetTickCount64() + timeout;
+            for (;;)
+            {
+                MSG msg;
+                while (::PeekMessage(&msg, nullptr, 0, 0, PM_REMOVE))
+                {
+                    if (msg.message == WM_QUIT)
+                    {
+                        return {EventType::Quit, ""};
+                    }
+
+                    ::TranslateMessage(&msg);
+                    ::DispatchMessage(&msg);
+
+                    if (m_events.size() > 0)
+                    {
+                        auto event = m_events.front();
+                        m_events.pop_front();
+                        return event;
+                    }
+                }
+
+                const ULONGLONG now = ::GetTickCount64();
+                if (now >= deadline)
+                {
+                    return {EventType::None, ""};
+                }
+
+                ::MsgWaitForMultipleObjectsEx(0, nullptr, static_cast<DWORD>(deadline - now), QS_ALLINPUT, MWMO_INPUTAVAILABLE);
+            }
+        }
+    }
+
+    HWND GetHwnd() const
+    {
+        return m_hwnd;
+    }
+
+    void SetTitle(const std::string &title)
+    {
+        winrt::check_bool(::SetWindowTextW(m_hwnd, WideStringFromString(title).c_str()));
+    }
+
+    // Outer window size, as with the minimum track size of WM_GETMINMAXINFO.
+    void SetMinSize(int32_t width, int32_t height)
+    {
+        m_minWidth = width;
+        m_minHeight = height;
+
+        // Resizing to the current size goes through WM_GETMINMAXINFO again,
+        // so the window snaps into the new bounds right away.
+        RECT rect;
+        ::GetWindowRect(m_hwnd, &rect);
+        ::SetWindowPos(
+            m_hwnd,
+            nullptr,
+            0,
+            0,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+            SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE);
     }
 
     void Dispatch(void *webview, void *callback)
     {
         auto info = std::make_unique<DispatchInfo>(DispatchInfo{webview, callback});
-        PostMessage(m_hwnd, WM_APP_DISPATCH, 0, reinterpret_cast<LPARAM>(info.release()));
+        winrt::check_bool(PostMessage(m_hwnd, WM_APP_DISPATCH, 0, reinterpret_cast<LPARAM>(info.get())));
+        info.release();
     }
 
 private:
@@ -335,19 +634,59 @@ private:
         webview_dispatch_callback(info->webview, info->callback);
     }
 
+    void _CancelPendingDispatches()
+    {
+        MSG msg;
+        while (::PeekMessage(&msg, m_hwnd, WM_APP_DISPATCH, WM_APP_DISPATCH, PM_REMOVE))
+        {
+            std::unique_ptr<DispatchInfo> info(reinterpret_cast<DispatchInfo *>(msg.lParam));
+            webview_dispatch_cancel(info->webview, info->callback);
+        }
+    }
+
     LRESULT _HandleMessage(UINT msg, WPARAM wParam, LPARAM lParam)
     {
         switch (msg)
         {
         case WM_DESTROY:
+            _CancelPendingDispatches();
             ::PostQuitMessage(0);
             break;
         case WM_SIZE:
             _UpdateBounds();
+            if (!m_sizing && wParam != SIZE_MINIMIZED)
+            {
+                _QueueResized();
+            }
+            break;
+        case WM_ENTERSIZEMOVE:
+            m_sizing = true;
+            break;
+        case WM_EXITSIZEMOVE:
+            m_sizing = false;
+            _QueueResized();
             break;
         case WM_APP_DISPATCH:
             _HandleDispatch(lParam);
             break;
+        case WM_DROPFILES:
+            _QueueFileDrop(reinterpret_cast<HDROP>(wParam));
+            break;
+        case WM_GETMINMAXINFO:
+        {
+            // A zero minimum keeps the system's own, which stops the
+            // window from shrinking past its caption buttons.
+            auto info = reinterpret_cast<MINMAXINFO *>(lParam);
+            if (m_minWidth > 0)
+            {
+                info->ptMinTrackSize.x = m_minWidth;
+            }
+            if (m_minHeight > 0)
+            {
+                info->ptMinTrackSize.y = m_minHeight;
+            }
+            break;
+        }
         default:
             return DefWindowProc(m_hwnd, msg, wParam, lParam);
         }
@@ -355,6 +694,59 @@ private:
         return 0;
     }
 
+    // Only the latest size matters, so a pending Resized event is updated in
+    // place rather than queueing another one. Interactive resizing is reported
+    // once, when the drag ends.
+    void _QueueResized()
+    {
+        RECT clientRect;
+        ::GetClientRect(m_hwnd, &clientRect);
+        const int32_t width = clientRect.right - clientRect.left;
+        const int32_t height = clientRect.bottom - clientRect.top;
+
+        if (width == m_lastWidth && height == m_lastHeight)
+        {
+            return;
+        }
+        m_lastWidth = width;
+        m_lastHeight = height;
+
+        for (auto &event : m_events)
+        {
+            if (event.type == EventType::Resized)
+            {
+                event.width = width;
+                event.height = height;
+                return;
+            }
+        }
+
+        m_events.push_back({EventType::Resized, "", width, height});
+    }
+
+    // Paths can't contain control characters, so they're sent newline-separated.
+    void _QueueFileDrop(HDROP drop)
+    {
+        std::string paths;
+        const UINT count = ::DragQueryFileW(drop, 0xFFFFFFFF, nullptr, 0);
+        for (UINT i = 0; i < count; i++)
+        {
+            std::wstring path(::DragQueryFileW(drop, i, nullptr, 0) + 1, L'\0');
+            path.resize(::DragQueryFileW(drop, i, &path[0], static_cast<UINT>(path.size())));
+            if (i > 0)
+            {
+                paths += '\n';
+            }
+            paths += winrt::to_string(path);
+        }
+        ::DragFinish(drop);
+
+        if (count > 0)
+        {
+            m_events.push_back({EventType::FileDrop, paths});
+        }
+    }
+
     void OnDOMContentLoaded(const IWebViewControl &, const WebViewControlDOMContentLoadedEventArgs &)
     {
         m_events.push_back({EventType::DOMContentLoaded, ""});
@@ -371,6 +763,11 @@ private:
     winrt::Windows::Web::UI::Interop::WebViewControl m_control = nullptr;
     bool m_injectCssFunctionInitialized = false;
     std::deque<EventInfo> m_events;
+    bool m_sizing = false;
+    int32_t m_lastWidth = -1;
+    int32_t m_lastHeight = -1;
+    int32_t m_minWidth = 0;
+    int32_t m_minHeight = 0;
     winrt::event_revoker<IWebViewControl> m_domContentLoadedRevoker;
     winrt::event_revoker<IWebViewControl> m_scriptNotifyRevoker;
 };
@@ -496,6 +893,28 @@ HRESULT webview_navigate(void *window, void *webview, const char *content, Conte
     });
 }
 
+HRESULT webview_navigate_with_request(
+    void *window,
+    void *webview,
+    const char *url,
+    const char *method,
+    const char *const *header_names,
+    const char *const *header_values,
+    size_t header_count,
+    const uint8_t *body,
+    size_t body_length) noexcept
+{
+    return MapException(window, [=](Window &window) {
+        if (webview == nullptr || url == nullptr || method == nullptr ||
+            (header_count > 0 && (header_names == nullptr || header_values == nullptr)))
+        {
+            winrt::throw_hresult(E_INVALIDARG);
+        }
+
+        window.NavigateWithRequest(webview, url, method, header_names, header_values, header_count, body, body_length);
+    });
+}
+
 HRESULT webview_navigate_with_streamresolver(void *window, void *webview, const char *source) noexcept
 {
     return MapException(window, [webview, source](Window &window) {
@@ -508,16 +927,20 @@ HRESULT webview_navigate_with_streamresolver(void *window, void *webview, const
     });
 }
 
-HRESULT webview_loop(void *window, bool blocking, EventType *event, char **data) noexcept
+HRESULT webview_loop(void *window, uint32_t timeout, EventType *event, char **data, int32_t *width, int32_t *height) noexcept
 {
     *event = EventType::None;
     *data = nullptr;
+    *width = 0;
+    *height = 0;
 
-    return MapException(window, [blocking, event, data](Window &window) {
-        auto info = window.Loop(blocking);
+    return MapException(window, [timeout, event, data, width, height](Window &window) {
+        auto info = window.Loop(timeout);
 
         *event = info.type;
         *data = !info.data.empty() ? webview_string_new(info.data) : nullptr;
+        *width = info.width;
+        *height = info.height;
     });
 }
 
@@ -543,18 +966,96 @@ HRESULT webview_eval_script(void *window, const char *script, char **value) noex
     });
 }
 
-HRESULT webview_inject_css(void *window, const char *css) noexcept
+HRESULT webview_set_title(void *window, const char *title) noexcept
+{
+    return MapException(window, [title](Window &window) {
+        if (title == nullptr)
+        {
+            winrt::throw_hresult(E_INVALIDARG);
+        }
+
+        window.SetTitle(title);
+    });
+}
+
+HRESULT webview_get_hwnd(void *window, void **hwnd) noexcept
+{
+    *hwnd = nullptr;
+
+    return MapException(window, [hwnd](Window &window) {
+        *hwnd = window.GetHwnd();
+    });
+}
+
+HRESULT webview_set_min_size(void *window, int32_t width, int32_t height) noexcept
+{
+    return MapException(window, [width, height](Window &window) {
+        if (width < 0 || height < 0)
+        {
+            winrt::throw_hresult(E_INVALIDARG);
+        }
+
+        window.SetMinSize(width, height);
+    });
+}
+
+// EdgeHTML has no in-process developer tools. The DevTools Preview app runs
+// separately and attaches to the web views of this process on its own.
+HRESULT webview_open_dev_tools(void *window) noexcept
+{
+    return MapException(window, [](Window &) {
+        auto result = reinterpret_cast<INT_PTR>(::ShellExecuteW(
+            nullptr,
+            L"open",
+            L"shell:AppsFolder\\Microsoft.MicrosoftEdgeDevToolsPreview_8wekyb3d8bbwe!App",
+            nullptr,
+            nullptr,
+            SW_SHOWNORMAL));
+        if (result <= 32)
+        {
+            winrt::throw_last_error();
+        }
+    });
+}
+
+HRESULT webview_inject_css(void *window, const char *css, uint32_t id) noexcept
 {
-    return MapException(window, [css](Window &window) {
+    return MapException(window, [css, id](Window &window) {
         if (css == nullptr)
         {
             winrt::throw_hresult(E_INVALIDARG);
         }
 
-        window.InjectCss(css);
+        window.InjectCss(css, id);
     });
 }
 
+HRESULT webview_new_stream_resolver(void *context, webview_content_fn content, void (*free_context)(void *context), void **resolver) noexcept
+{
+    *resolver = nullptr;
+    s_error.reset();
+
+    try
+    {
+        winrt::check_pointer(content);
+        IUriToStreamResolver created = winrt::make<UriToStreamResolver>(context, content, free_context);
+        *resolver = winrt::detach_abi(created);
+    }
+    catch (const winrt::hresult_error &err)
+    {
+        s_error = err;
+        return err.code();
+    }
+    catch (...)
+    {
+        auto hr = to_hresult();
+        s_error = winrt::hresult_error(hr);
+        return hr;
+    }
+
+    return S_OK;
+}
+
 HRESULT webview_get_error_message(char **message) noexcept
 {
     *message = nullptr;
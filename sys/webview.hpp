@@ -16,17 +16,29 @@ extern "C"
         None,
         Quit,
         DOMContentLoaded,
-        ScriptNotify
+        ScriptNotify,
+        Resized,
+        FileDrop
     };
 
+    // Looks up `path` and sets `total_length` to its full size and `content` to
+    // at most `max_length` bytes of it starting at `offset`.
+    typedef bool (*webview_content_fn)(void *context, const char *path, uint64_t offset, uint64_t max_length, const uint8_t **content, size_t *length, uint64_t *total_length, const char **mime);
+
     HRESULT webview_new(const char *title, int32_t width, int32_t height, bool resizable, void **window) noexcept;
     void webview_free(void *window) noexcept;
     void webview_string_free(const char *str) noexcept;
     HRESULT webview_navigate(void *window, void *webview, const char *content, ContentType contentType) noexcept;
+    HRESULT webview_navigate_with_request(void *window, void *webview, const char *url, const char *method, const char *const *header_names, const char *const *header_values, size_t header_count, const uint8_t *body, size_t body_length) noexcept;
     HRESULT webview_navigate_with_streamresolver(void *window, void *webview, const char *source) noexcept;
-    HRESULT webview_loop(void *window, bool blocking, EventType *event, char **data) noexcept;
+    HRESULT webview_loop(void *window, uint32_t timeout, EventType *event, char **data, int32_t *width, int32_t *height) noexcept;
     HRESULT webview_dispatch(void *window, void *webview, void *callback) noexcept;
     HRESULT webview_eval_script(void *window, const char *script, char **value) noexcept;
-    HRESULT webview_inject_css(void *window, const char *css) noexcept;
+    HRESULT webview_set_title(void *window, const char *title) noexcept;
+    HRESULT webview_get_hwnd(void *window, void **hwnd) noexcept;
+    HRESULT webview_set_min_size(void *window, int32_t width, int32_t height) noexcept;
+    HRESULT webview_open_dev_tools(void *window) noexcept;
+    HRESULT webview_inject_css(void *window, const char *css, uint32_t id) noexcept;
+    HRESULT webview_new_stream_resolver(void *context, webview_content_fn content, void (*free_context)(void *context), void **resolver) noexcept;
     HRESULT webview_get_error_message(char **message) noexcept;
 }
\ No newline at end of file